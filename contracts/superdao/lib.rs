@@ -5,26 +5,36 @@
 // - need failing tests
 // - need getters - mostly done
 // - e2e tests
-// - limit registering to contract addresses only <- if gov token, maybe not
-// - emit events
 
 #[ink::contract]
 mod superdao {
     use ink::codegen::Env;
     use ink::{
         env::{
-            call::{build_call, ExecutionInput},
+            call::{build_call, build_create, ExecutionInput, FromAccountId, Selector},
             CallFlags,
         },
-        prelude::vec::Vec,
+        prelude::{vec, vec::Vec},
         scale::{Decode, Encode, Output},
         storage::Mapping,
         xcm::prelude::*,
     };
     use superdao_traits::{
-        Call, ChainCall, ContractCall, Error, Proposal, SuperDao, SuperDaoQuery, Vote,
+        ballot_payload, quadratic_weight, vote_commitment, Call, CallResult, ChainCall,
+        ContractCall, Conviction, Error, GovernanceRule, InstantiateCall, InternalCall,
+        MembershipMode, MembershipPolicy, Perbill, Proposal, ProposalMetadata, ProposalStatus,
+        Signature, SuperDao, SuperDaoQuery, Tally, Vote, VotingMode,
     };
 
+    /// Deepest nesting of batches a proposal may contain.
+    const MAX_BATCH_DEPTH: u32 = 4;
+
+    /// Version of the storage layout, to be increased by upgrades that need a migration.
+    const STORAGE_VERSION: u32 = 1;
+
+    /// Storage key of the storage layout version, kept out of the contract struct.
+    const STORAGE_VERSION_KEY: u32 = 0x7665_7273;
+
     /// A wrapper that allows us to encode a blob of bytes.
     ///
     /// We use this to pass the set of untyped (bytes) parameters to the `CallBuilder`.
@@ -37,294 +47,3945 @@ mod superdao {
         }
     }
 
+    /// The return value of a contract call, whose type is unknown to the DAO.
+    ///
+    /// We use this to accept whatever the callee returns, as only the success of the call
+    /// matters; a message returning an error reverts and fails the call.
+    struct CallOutput;
+
+    impl Decode for CallOutput {
+        fn decode<I: ink::scale::Input>(input: &mut I) -> Result<Self, ink::scale::Error> {
+            let mut byte = [0u8];
+            while input.remaining_len()?.unwrap_or_default() > 0 {
+                input.read(&mut byte)?;
+            }
+            Ok(Self)
+        }
+    }
+
+    /// A contract instantiated by a proposal, whose interface is unknown to the DAO.
+    struct InstantiatedContract(AccountId);
+
+    impl ink::env::ContractEnv for InstantiatedContract {
+        type Env = Environment;
+    }
+
+    impl FromAccountId<Environment> for InstantiatedContract {
+        fn from_account_id(account_id: AccountId) -> Self {
+            Self(account_id)
+        }
+    }
+
+    /// Emitted when an account registers as a member.
+    #[ink(event)]
+    pub struct MemberRegistered {
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    /// Emitted when a member leaves the DAO.
+    #[ink(event)]
+    pub struct MemberDeregistered {
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    /// Emitted when an account applies for membership.
+    #[ink(event)]
+    pub struct MembershipApplied {
+        #[ink(topic)]
+        applicant: AccountId,
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted when a new proposal is created.
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        proposer: AccountId,
+        call: Call,
+        voting_period_end: BlockNumber,
+    }
+
+    /// Emitted when a member votes on a proposal for the first time.
+    #[ink(event)]
+    pub struct VoteCast {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        voter: AccountId,
+        vote: Vote,
+        weight: u128,
+    }
+
+    /// Emitted when a member commits to a vote in commit-reveal mode.
+    #[ink(event)]
+    pub struct VoteCommitted {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        voter: AccountId,
+    }
+
+    /// Emitted when a member replaces its previous vote on a proposal.
+    #[ink(event)]
+    pub struct VoteChanged {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        voter: AccountId,
+        vote: Vote,
+        weight: u128,
+    }
+
+    /// Emitted when value is locked for a conviction vote.
+    #[ink(event)]
+    pub struct Locked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        until: BlockNumber,
+    }
+
+    /// Emitted when locked value is returned.
+    #[ink(event)]
+    pub struct Unlocked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when a member delegates its vote.
+    #[ink(event)]
+    pub struct Delegated {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+    }
+
+    /// Emitted when a member revokes its delegation.
+    #[ink(event)]
+    pub struct Undelegated {
+        #[ink(topic)]
+        delegator: AccountId,
+    }
+
+    /// Emitted when a proposal passes the vote.
+    #[ink(event)]
+    pub struct ProposalApproved {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted when a proposal fails the vote.
+    #[ink(event)]
+    pub struct ProposalRejected {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted when the call of an approved proposal is scheduled for dispatch.
+    #[ink(event)]
+    pub struct ProposalScheduled {
+        #[ink(topic)]
+        proposal_id: u32,
+        enactment_block: BlockNumber,
+    }
+
+    /// Emitted when a proposal was not resolved or executed in time.
+    #[ink(event)]
+    pub struct ProposalExpired {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted when a proposer withdraws its proposal.
+    #[ink(event)]
+    pub struct ProposalCancelled {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted when a member vetoes a proposal.
+    #[ink(event)]
+    pub struct VetoCast {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        voter: AccountId,
+    }
+
+    /// Emitted once enough members vetoed a proposal.
+    #[ink(event)]
+    pub struct ProposalVetoed {
+        #[ink(topic)]
+        proposal_id: u32,
+    }
+
+    /// Emitted after the call of an approved proposal has been dispatched, or could not be.
+    #[ink(event)]
+    pub struct CallDispatched {
+        #[ink(topic)]
+        proposal_id: u32,
+        success: bool,
+    }
+
     #[ink(storage)]
     #[derive(Default)]
     pub struct Superdao {
         members: Vec<AccountId>,
         proposals: Mapping<u32, Proposal>,
+        statuses: Mapping<u32, ProposalStatus>,
+        /// Block from which each approved proposal can be executed.
+        enactments: Mapping<u32, BlockNumber>,
+        /// Outcome of each call dispatched by an executed proposal.
+        execution_results: Mapping<u32, Vec<CallResult>>,
         active_proposals: Vec<u32>,
-        votes: Mapping<u32, Vec<(AccountId, Vote)>>,
+        /// Votes per proposal together with their weight.
+        votes: Mapping<u32, Vec<(AccountId, Vote, u128)>>,
+        /// Members at the time each proposal was created; only they can vote on it.
+        electorates: Mapping<u32, Vec<AccountId>>,
         next_id: u32,
         vote_threshold: u8,
+        governance_rule: GovernanceRule,
         voting_period: BlockNumber,
+        /// Amount that has to be transferred to `register_member`, refunded on deregistration.
+        membership_deposit: Balance,
+        deposits: Mapping<AccountId, Balance>,
+        /// Value held for members, their deposits and locked votes, which proposals cannot
+        /// spend.
+        reserved: Balance,
+        membership_mode: MembershipMode,
+        /// Pending membership applications and the proposal admitting the applicant. The
+        /// deposit of the applicant is escrowed in `deposits`.
+        applications: Mapping<AccountId, u32>,
+        membership_policy: MembershipPolicy,
+        allowed_code_hashes: Vec<Hash>,
+        voting_mode: VotingMode,
+        delegations: Mapping<AccountId, AccountId>,
+        /// Delegations between members at the time each proposal was created; only they count
+        /// on it.
+        delegation_snapshots: Mapping<u32, Vec<(AccountId, AccountId)>>,
+        /// Weight of each delegator per proposal, taken when a delegate votes on its behalf.
+        delegated_weights: Mapping<(u32, AccountId), u128>,
+        /// Value locked by conviction votes and the block from which it can be unlocked.
+        locks: Mapping<AccountId, (Balance, BlockNumber)>,
+        /// Quadratic voting credits used per member and credit period.
+        used_credits: Mapping<(AccountId, u32), u128>,
+        /// Quadratic voting credits spent per proposal and member.
+        spent_credits: Mapping<(u32, AccountId), u128>,
+        /// Vote commitments per proposal and member in commit-reveal mode, together with the
+        /// value locked alongside them.
+        commitments: Mapping<(u32, AccountId), ([u8; 32], Balance)>,
+        /// Number of signed ballots accepted per member, protecting against replays.
+        ballot_nonces: Mapping<AccountId, u64>,
+        /// Members that vetoed each proposal.
+        vetoes: Mapping<u32, Vec<AccountId>>,
     }
 
     impl Superdao {
         #[ink(constructor, payable)]
-        pub fn new(vote_threshold: u8, voting_period: BlockNumber) -> Self {
-            Self {
+        pub fn new(
+            vote_threshold: u8,
+            voting_period: BlockNumber,
+            governance_rule: GovernanceRule,
+            membership_deposit: Balance,
+            membership_mode: MembershipMode,
+            membership_policy: MembershipPolicy,
+            voting_mode: VotingMode,
+        ) -> Result<Self, Error> {
+            // quadratic votes are weighed by the credits spent on them, which cannot be
+            // committed to
+            if governance_rule.reveal_period > 0
+                && matches!(voting_mode, VotingMode::Quadratic { .. })
+            {
+                return Err(Error::InvalidVotingMode);
+            }
+            let dao = Self {
                 members: Vec::new(),
                 proposals: Mapping::new(),
+                statuses: Mapping::new(),
+                enactments: Mapping::new(),
+                execution_results: Mapping::new(),
                 active_proposals: Vec::new(),
                 votes: Mapping::new(),
+                electorates: Mapping::new(),
                 next_id: 0,
                 vote_threshold,
+                governance_rule,
                 voting_period,
-            }
+                membership_deposit,
+                deposits: Mapping::new(),
+                reserved: 0,
+                membership_mode,
+                applications: Mapping::new(),
+                membership_policy,
+                allowed_code_hashes: Vec::new(),
+                voting_mode,
+                delegations: Mapping::new(),
+                delegation_snapshots: Mapping::new(),
+                delegated_weights: Mapping::new(),
+                locks: Mapping::new(),
+                used_credits: Mapping::new(),
+                spent_credits: Mapping::new(),
+                commitments: Mapping::new(),
+                ballot_nonces: Mapping::new(),
+                vetoes: Mapping::new(),
+            };
+            ink::env::set_contract_storage(&STORAGE_VERSION_KEY, &STORAGE_VERSION);
+            Ok(dao)
         }
 
         #[ink(constructor, payable)]
         pub fn default() -> Self {
+            ink::env::set_contract_storage(&STORAGE_VERSION_KEY, &STORAGE_VERSION);
             Default::default()
         }
 
+        /// Tallies the votes of a proposal whose voting period has ended. With a supermajority
+        /// rule, the proposal can be resolved earlier once the supermajority decided it.
+        ///
+        /// An approved proposal is scheduled for `execute_proposal` after the enactment delay,
+        /// or dispatched right away if there is none. A proposal resolved after its execution
+        /// window expires instead.
+        ///
+        /// The proposal and its votes are kept in storage; the outcome is recorded as the
+        /// proposal's status and returned.
         #[ink(message)]
-        pub fn resolve_proposal(&mut self, prop_id: u32) -> Result<bool, Error> {
-            assert!(
-                self.ensure_proposal_exists(prop_id).is_ok(),
-                "Proposal does not exist."
-            );
+        pub fn resolve_proposal(&mut self, prop_id: u32) -> Result<ProposalStatus, Error> {
+            self.ensure_active(prop_id)?;
 
-            let proposal = self
-                .proposals
-                .take(prop_id)
-                .expect("Proposal existence confirmed above; qed");
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            if self.is_expired(prop_id, &proposal) {
+                self.active_proposals.retain(|&x| x != prop_id);
+                return self.expire(prop_id);
+            }
+            let votes = self.counted_votes(prop_id);
+            let approved = if self.env().block_number() < self.reveal_period_end(&proposal) {
+                self.early_outcome(prop_id, &proposal, &votes)
+                    .ok_or(Error::VotingPeriodNotEnded)?
+            } else {
+                // a proposal nobody voted on is rejected
+                self.votes.contains(prop_id) && self.is_approved(&proposal, &votes)
+            };
 
             self.active_proposals.retain(|&x| x != prop_id);
 
-            assert!(
-                self.env().block_number() >= proposal.voting_period_end,
-                "Proposal not ready to execute"
-            );
-
-            let votes = self.votes.take(prop_id).expect("No votes yet");
+            if !approved {
+                self.statuses.insert(prop_id, &ProposalStatus::Rejected);
+                self.refund_application(prop_id)?;
+                self.env().emit_event(ProposalRejected {
+                    proposal_id: prop_id,
+                });
+                return Ok(ProposalStatus::Rejected);
+            }
+            self.statuses.insert(prop_id, &ProposalStatus::Approved);
+            self.env().emit_event(ProposalApproved {
+                proposal_id: prop_id,
+            });
 
-            let total_ayes = votes.iter().filter(|(_, vote)| vote == &Vote::Aye).count() as u8;
+            let enactment_delay = self.governance_rule.enactment_delay;
+            if enactment_delay > 0 {
+                let enactment_block = self.env().block_number().saturating_add(enactment_delay);
+                self.enactments.insert(prop_id, &enactment_block);
+                self.env().emit_event(ProposalScheduled {
+                    proposal_id: prop_id,
+                    enactment_block,
+                });
+                return Ok(ProposalStatus::Approved);
+            }
+            self.execute(prop_id, proposal.call)
+        }
 
-            if total_ayes < self.vote_threshold {
+        /// Dispatches the call of an approved proposal once its enactment delay has passed.
+        #[ink(message)]
+        pub fn execute_proposal(&mut self, prop_id: u32) -> Result<ProposalStatus, Error> {
+            self.ensure_proposal_exists(prop_id)?;
+            if self.statuses.get(prop_id) != Some(ProposalStatus::Approved) {
                 return Err(Error::ProposalIsNotApproved);
             }
-            self.dispatch_call(proposal.call)
+            let enactment_block = self.enactments.get(prop_id).unwrap_or_default();
+            if self.env().block_number() < enactment_block {
+                return Err(Error::EnactmentDelayNotPassed);
+            }
+
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            if self.is_expired(prop_id, &proposal) {
+                return self.expire(prop_id);
+            }
+            self.execute(prop_id, proposal.call)
         }
 
-        #[cfg(test)]
-        fn dispatch_call(&self, call: Call) -> Result<(), Error> {
-            Ok(())
+        /// Block from which a proposal can be executed: the enactment delay after its approval,
+        /// or after the end of its vote while it is not resolved yet.
+        fn enactment_block(&self, prop_id: u32, proposal: &Proposal) -> BlockNumber {
+            self.enactments.get(prop_id).unwrap_or_else(|| {
+                self.reveal_period_end(proposal)
+                    .saturating_add(self.governance_rule.enactment_delay)
+            })
         }
-        #[cfg(not(test))]
-        fn dispatch_call(&self, call: Call) -> Result<bool, Error> {
-            match call {
-                Call::Contract(call) => {
-                    // source: https://github.com/use-ink/ink-examples/blob/main/multisig/lib.rs#L541
-                    let call_flags = if call.allow_reentry {
-                        CallFlags::ALLOW_REENTRY
-                    } else {
-                        CallFlags::empty()
-                    };
 
-                    let result = build_call::<<Self as ::ink::env::ContractEnv>::Env>()
-                        .call(call.callee)
-                        .ref_time_limit(call.ref_time_limit)
-                        .transferred_value(call.transferred_value)
-                        .call_flags(call_flags)
-                        .exec_input(
-                            ExecutionInput::new(call.selector.into())
-                                .push_arg(CallInput(&call.input)),
-                        )
-                        .returns::<()>()
-                        .try_invoke();
-                    return Ok(result.is_ok());
-                }
-                Call::Chain(call) => {
-                    let dest = call.get_dest();
-                    let msg = call.get_msg();
-
-                    // TODO: proper error handling
-                    // use xcm_execute if dest is local chain, otherwise xcm_send
-                    let was_success = if dest == Location::here() {
-                        self.env().xcm_execute(&VersionedXcm::V4(msg)).is_ok()
-                    } else {
-                        self.env()
-                            .xcm_send(&VersionedLocation::V4(dest), &VersionedXcm::V4(msg))
-                            .is_ok()
-                    };
+        /// Whether the execution window following the enactment block of a proposal has passed.
+        fn is_expired(&self, prop_id: u32, proposal: &Proposal) -> bool {
+            let execution_window = self.governance_rule.execution_window;
+            if execution_window == 0 {
+                return false;
+            }
+            let expiry = self
+                .enactment_block(prop_id, proposal)
+                .saturating_add(execution_window);
+            self.env().block_number() >= expiry
+        }
 
-                    return Ok(was_success);
+        /// Status of a proposal, in which a proposal whose execution window passed is expired
+        /// even before it is marked as such.
+        fn status(&self, prop_id: u32) -> Option<ProposalStatus> {
+            let status = self.statuses.get(prop_id)?;
+            if matches!(status, ProposalStatus::Active | ProposalStatus::Approved) {
+                let proposal = self.proposals.get(prop_id)?;
+                if self.is_expired(prop_id, &proposal) {
+                    return Some(ProposalStatus::Expired);
                 }
             }
+            Some(status)
         }
 
-        fn ensure_member(&self) -> Result<(), Error> {
-            if !self.is_member() {
-                return Err(Error::NotMember);
+        fn expire(&mut self, prop_id: u32) -> Result<ProposalStatus, Error> {
+            self.enactments.remove(prop_id);
+            self.statuses.insert(prop_id, &ProposalStatus::Expired);
+            self.refund_application(prop_id)?;
+            self.env().emit_event(ProposalExpired {
+                proposal_id: prop_id,
+            });
+            Ok(ProposalStatus::Expired)
+        }
+
+        /// Refunds the deposit escrowed with a membership application that has been closed
+        /// without admitting the applicant.
+        fn refund_application(&mut self, prop_id: u32) -> Result<(), Error> {
+            let Some(Proposal {
+                call: Call::Internal(InternalCall::AddMember(applicant)),
+                ..
+            }) = self.proposals.get(prop_id)
+            else {
+                return Ok(());
+            };
+            // admitting the applicant already closed the application
+            if self.applications.get(applicant) != Some(prop_id) {
+                return Ok(());
+            }
+            self.applications.remove(applicant);
+            if let Some(deposit) = self.deposits.take(applicant) {
+                self.reserved = self.reserved.saturating_sub(deposit);
+                self.env()
+                    .transfer(applicant, deposit)
+                    .map_err(|_| Error::TransferFailed)?;
             }
             Ok(())
         }
 
-        fn ensure_proposal_exists(&self, prop_id: u32) -> Result<(), Error> {
-            if !self.proposals.contains(prop_id) {
-                return Err(Error::ProposalNotFound);
+        /// Dispatches the call of an approved proposal and records the outcome.
+        ///
+        /// Fails, reverting the dispatch, if the calls spent the reserved balance.
+        fn execute(&mut self, prop_id: u32, call: Call) -> Result<ProposalStatus, Error> {
+            let mut results = Vec::new();
+            let dispatched = self.dispatch(call, &mut results);
+            // what runtime calls and XCM programs spend is only known after their dispatch
+            if self.env().balance() < self.reserved {
+                return Err(Error::SpendsReservedBalance);
             }
-            Ok(())
+            let status = match dispatched {
+                Ok(success) => {
+                    self.env().emit_event(CallDispatched {
+                        proposal_id: prop_id,
+                        success,
+                    });
+                    ProposalStatus::Executed { success }
+                }
+                Err(_) => {
+                    self.env().emit_event(CallDispatched {
+                        proposal_id: prop_id,
+                        success: false,
+                    });
+                    ProposalStatus::Failed
+                }
+            };
+            self.statuses.insert(prop_id, &status);
+            self.execution_results.insert(prop_id, &results);
+            self.refund_application(prop_id)?;
+            Ok(status)
         }
 
-        fn find_vote(&self, votes: &Vec<(AccountId, Vote)>) -> Option<usize> {
-            votes.iter().position(|(x, _)| x == &self.env().caller())
+        /// Dispatches the calls of an atomic batch, failing and thereby reverting all of them
+        /// if any of them fails. Only callable by the DAO itself.
+        #[ink(message)]
+        pub fn dispatch_atomic_batch(
+            &mut self,
+            calls: Vec<Call>,
+        ) -> Result<Vec<CallResult>, Error> {
+            if self.env().caller() != self.env().account_id() {
+                return Err(Error::NotSelf);
+            }
+            self.dispatch_all(calls)
         }
-    }
 
-    impl SuperDao for Superdao {
+        /// Migrates the storage written by an earlier version of the contract to the current
+        /// layout. Only callable by the DAO itself: a proposal upgrading the DAO dispatches it
+        /// as a contract call to the DAO, allowing reentry, right after the
+        /// `InternalCall::SetCodeHash` in the same atomic batch.
+        ///
+        /// The contract struct is still written back by the code that executes the proposal,
+        /// so upgrades must keep its layout and migrate only state held in mappings.
         #[ink(message)]
-        fn register_member(&mut self) -> Result<(), Error> {
-            if self.is_member() {
-                return Err(Error::AlreadyMember);
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            if self.env().caller() != self.env().account_id() {
+                return Err(Error::NotSelf);
             }
-            self.members.push(self.env().caller());
+            // version 1 is the first versioned layout, later versions migrate the storage of
+            // the versions before them here, going by `get_storage_version`
+            ink::env::set_contract_storage(&STORAGE_VERSION_KEY, &STORAGE_VERSION);
             Ok(())
         }
 
-        #[ink(message)]
-        fn deregister_member(&mut self) {
-            let caller = self.env().caller();
-            self.members.retain(|&x| x != caller);
+        /// Checks the votes against the absolute `vote_threshold` as well as the quorum and
+        /// approval ratios of the governance rule.
+        ///
+        /// The threshold and quorum count voters, the approval ratio is evaluated on the vote
+        /// weights of Ayes and Nays. Abstentions only count toward the quorum.
+        fn is_approved(&self, proposal: &Proposal, votes: &[(AccountId, Vote, u128)]) -> bool {
+            let aye_voters = votes
+                .iter()
+                .filter(|(_, vote, _)| vote == &Vote::Aye)
+                .count() as u128;
+            let tally = Self::tally(votes);
+
+            aye_voters >= u128::from(self.vote_threshold)
+                && self
+                    .governance_rule
+                    .quorum
+                    .is_reached_by(tally.turnout.into(), proposal.electorate_size.into())
+                && self
+                    .governance_rule
+                    .approval
+                    .is_reached_by(tally.ayes, tally.ayes.saturating_add(tally.nays))
         }
 
-        #[ink(message)]
-        fn create_proposal(&mut self, call: Call) -> Result<u32, Error> {
-            let id = self.next_id;
-            self.ensure_member()?;
+        /// Outcome of a proposal before the end of its voting period: `Some(true)` if a
+        /// supermajority of its electorate voted Aye and the proposal is approved, `Some(false)`
+        /// if a supermajority voted Nay and `None` if the vote is still open. Either outcome
+        /// requires that the members who did not vote directly cannot change it anymore.
+        fn early_outcome(
+            &self,
+            prop_id: u32,
+            proposal: &Proposal,
+            votes: &[(AccountId, Vote, u128)],
+        ) -> Option<bool> {
+            let supermajority = self.governance_rule.supermajority?;
+            if self.is_commit_reveal() {
+                return None;
+            }
 
-            let proposal = Proposal {
-                call,
-                voting_period_end: self.env().block_number().saturating_add(self.voting_period),
+            // delegated votes are not final, delegators can still vote themselves
+            let direct = self.votes.get(prop_id).unwrap_or_default();
+            let remaining = u128::from(proposal.electorate_size)
+                .saturating_sub(direct.len() as u128)
+                .saturating_mul(self.max_voting_weight()?);
+            let Tally { ayes, nays, .. } = Self::tally(&direct);
+            let approval = self.governance_rule.approval;
+            let total = ayes.saturating_add(nays).saturating_add(remaining);
+
+            let voters = |expected: Vote| {
+                votes
+                    .iter()
+                    .filter(|(_, vote, _)| vote == &expected)
+                    .count() as u128
             };
+            let electorate_size = proposal.electorate_size.into();
+            if supermajority.is_reached_by(voters(Vote::Aye), electorate_size)
+                && self.is_approved(proposal, votes)
+                && approval.is_reached_by(ayes, total)
+            {
+                return Some(true);
+            }
+            if supermajority.is_reached_by(voters(Vote::Nay), electorate_size)
+                && !approval.is_reached_by(ayes.saturating_add(remaining), total)
+            {
+                return Some(false);
+            }
+            None
+        }
 
-            self.proposals.insert(id, &proposal);
-            self.active_proposals.push(id);
-            self.next_id = id.saturating_add(1);
+        /// Highest weight a single member can vote with, `None` if it depends on a balance.
+        fn max_voting_weight(&self) -> Option<u128> {
+            match self.voting_mode {
+                VotingMode::OneMemberOneVote => Some(1),
+                VotingMode::TokenWeighted { .. } | VotingMode::LockWeighted => None,
+                VotingMode::Quadratic { credits, .. } => Some(quadratic_weight(credits)),
+            }
+        }
 
-            Ok(id)
-            // TODO: event!
+        /// Votes of a proposal cast by members of its electorate, including the votes of members
+        /// that did not vote themselves but delegated to someone who did.
+        fn counted_votes(&self, prop_id: u32) -> Vec<(AccountId, Vote, u128)> {
+            let electorate = self.electorates.get(prop_id).unwrap_or_default();
+            let direct: Vec<_> = self
+                .votes
+                .get(prop_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(voter, _, _)| electorate.contains(voter))
+                .collect();
+
+            let delegations = self.delegation_snapshots.get(prop_id).unwrap_or_default();
+            let mut counted = direct.clone();
+            for (delegator, _) in delegations.iter() {
+                if Self::find_vote(&direct, delegator).is_some() {
+                    continue;
+                }
+                if let Some(vote) = Self::delegated_vote(&delegations, &direct, *delegator) {
+                    let weight = self
+                        .delegated_weights
+                        .get((prop_id, *delegator))
+                        .unwrap_or_default();
+                    counted.push((*delegator, vote, weight));
+                }
+            }
+            counted
         }
 
-        // TODO: vote enum type!
-        #[ink(message)]
-        fn vote(&mut self, prop_id: u32, vote: Vote) -> Result<(), Error> {
-            self.ensure_member()?;
-            self.ensure_proposal_exists(prop_id)?;
+        /// Follows the delegation chain of `delegator` up to the first delegate that voted.
+        fn delegated_vote(
+            delegations: &[(AccountId, AccountId)],
+            votes: &[(AccountId, Vote, u128)],
+            delegator: AccountId,
+        ) -> Option<Vote> {
+            let mut current = delegator;
+            // chains are acyclic, so they cannot be longer than the delegation list
+            for _ in 0..delegations.len() {
+                current = delegations
+                    .iter()
+                    .find(|(from, _)| from == &current)
+                    .map(|(_, to)| *to)?;
+                if let Some(index) = Self::find_vote(votes, &current) {
+                    return Some(votes[index].1.clone());
+                }
+            }
+            None
+        }
 
-            let mut votes = self.votes.get(&prop_id).unwrap_or_default();
-            let maybe_vote = self.find_vote(&votes);
+        /// The delegations of the given members, as snapshotted for a new proposal.
+        fn delegations_of(&self, members: &[AccountId]) -> Vec<(AccountId, AccountId)> {
+            members
+                .iter()
+                .filter_map(|member| self.delegations.get(member).map(|to| (*member, to)))
+                .collect()
+        }
 
-            match maybe_vote {
-                Some(index) => {
-                    votes[index].1 = vote;
+        /// Takes the weight of the members delegating to `delegate` on a proposal, directly or
+        /// through other delegates, so that later balance changes do not affect the vote.
+        fn snapshot_delegated_weights(&mut self, prop_id: u32, delegate: AccountId) {
+            let delegations = self.delegation_snapshots.get(prop_id).unwrap_or_default();
+            let mut delegates = vec![delegate];
+            let mut next = 0;
+            while next < delegates.len() {
+                let current = delegates[next];
+                next += 1;
+                for (delegator, to) in delegations.iter() {
+                    if to != &current || delegates.contains(delegator) {
+                        continue;
+                    }
+                    delegates.push(*delegator);
+                    if !self.delegated_weights.contains((prop_id, *delegator)) {
+                        // a failing weight query must not block the delegate's vote
+                        let weight = self.voting_weight(*delegator).unwrap_or_default();
+                        self.delegated_weights
+                            .insert((prop_id, *delegator), &weight);
+                    }
                 }
-                None => {
-                    votes.push((self.env().caller(), vote));
+            }
+        }
+
+        fn tally(votes: &[(AccountId, Vote, u128)]) -> Tally {
+            let mut tally = Tally::default();
+            for (_, vote, weight) in votes {
+                tally.add(vote, *weight);
+            }
+            tally
+        }
+
+        /// Weight of a vote cast by `voter` under the configured voting mode.
+        ///
+        /// Quadratic votes and votes weighed by locked value have no weight of their own, they
+        /// are cast through `vote_with_credits` and `vote_with_conviction`.
+        fn voting_weight(&self, voter: AccountId) -> Result<u128, Error> {
+            match self.voting_mode {
+                VotingMode::OneMemberOneVote => Ok(1),
+                VotingMode::TokenWeighted { token } => self.token_balance(token, voter),
+                VotingMode::Quadratic { .. } | VotingMode::LockWeighted => {
+                    Err(Error::InvalidVotingMode)
                 }
             }
+        }
 
-            self.votes.insert(prop_id, &votes);
-            Ok(())
+        fn is_lock_weighted(&self) -> bool {
+            matches!(self.voting_mode, VotingMode::LockWeighted)
         }
-    }
 
-    impl SuperDaoQuery for Superdao {
-        #[ink(message)]
-        fn get_members(&self) -> Vec<AccountId> {
-            self.members.clone()
+        /// Period in which the credits for a proposal are granted: the epoch the proposal was
+        /// created in, or the proposal itself if credits are granted per proposal.
+        fn credit_period(&self, prop_id: u32, epoch: BlockNumber) -> Result<u32, Error> {
+            if epoch == 0 {
+                return Ok(prop_id);
+            }
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            let created_at = proposal
+                .voting_period_end
+                .saturating_sub(self.voting_period);
+            Ok(created_at / epoch)
         }
 
-        #[ink(message)]
-        fn is_member(&self) -> bool {
-            self.members.contains(&self.env().caller())
+        #[cfg(test)]
+        fn token_balance(&self, _token: AccountId, owner: AccountId) -> Result<u128, Error> {
+            Ok(ink::env::test::get_account_balance::<Environment>(owner).unwrap_or_default())
+        }
+        #[cfg(not(test))]
+        fn token_balance(&self, token: AccountId, owner: AccountId) -> Result<u128, Error> {
+            build_call::<<Self as ::ink::env::ContractEnv>::Env>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                        .push_arg(owner),
+                )
+                .returns::<Balance>()
+                .try_invoke()
+                .map_err(|_| Error::TokenQueryFailed)?
+                .map_err(|_| Error::TokenQueryFailed)
         }
 
-        #[ink(message)]
-        fn get_proposal(&self, index: u32) -> Option<Proposal> {
-            self.proposals.get(index)
+        /// Dispatches `call`, appending the outcome of each dispatched call to `results`.
+        fn dispatch(&mut self, call: Call, results: &mut Vec<CallResult>) -> Result<bool, Error> {
+            let result = match call {
+                Call::Batch { calls, atomic } => {
+                    return self.dispatch_batch(calls, atomic, results)
+                }
+                Call::Instantiate(call) => {
+                    let address = if call.endowment > self.spendable_balance() {
+                        None
+                    } else {
+                        self.instantiate(call)
+                    };
+                    results.push(match address {
+                        Some(address) => CallResult::Instantiated(address),
+                        None => CallResult::Failed,
+                    });
+                    return Ok(address.is_some());
+                }
+                Call::Contract(call) => {
+                    if call.transferred_value > self.spendable_balance() {
+                        Err(Error::SpendsReservedBalance)
+                    } else {
+                        Ok(self.dispatch_contract_call(call))
+                    }
+                }
+                Call::Chain(call) => Ok(self.dispatch_chain_call(call)),
+                Call::Runtime(call) => Ok(self.dispatch_runtime_call(call)),
+                Call::Internal(call) => self.dispatch_internal(call),
+            };
+            results.push(match result {
+                Ok(success) => CallResult::Dispatched { success },
+                Err(_) => CallResult::Failed,
+            });
+            result
         }
 
-        #[ink(message)]
-        fn get_proposals(&self) -> Vec<(u32, Proposal)> {
-            self.active_proposals
-                .iter()
-                .map(|&x| {
-                    (
-                        x,
-                        self.proposals
-                            .get(x)
-                            .expect("If prop_id is present, proposal exists."),
-                    )
-                })
-                .collect()
+        /// Balance of the DAO that proposals can spend, without the reserved balance.
+        fn spendable_balance(&self) -> Balance {
+            self.env().balance().saturating_sub(self.reserved)
         }
 
-        #[ink(message)]
-        fn get_votes(&self, proposal_id: u32) -> Vec<(AccountId, Vote)> {
-            self.votes.get(proposal_id).unwrap_or_default()
+        /// Dispatches the calls of a batch; succeeds if all of them do.
+        fn dispatch_batch(
+            &mut self,
+            calls: Vec<Call>,
+            atomic: bool,
+            results: &mut Vec<CallResult>,
+        ) -> Result<bool, Error> {
+            if atomic {
+                return self.dispatch_atomic(calls, results);
+            }
+            let mut success = true;
+            for call in calls {
+                success &= self.dispatch(call, results).unwrap_or(false);
+            }
+            Ok(success)
         }
 
-        #[ink(message)]
-        fn get_vote_threshold(&self) -> u8 {
-            self.vote_threshold
+        /// Dispatches calls until one of them fails.
+        fn dispatch_all(&mut self, calls: Vec<Call>) -> Result<Vec<CallResult>, Error> {
+            let mut results = Vec::new();
+            for call in calls {
+                if !self.dispatch(call, &mut results)? {
+                    return Err(Error::DispatchFailed);
+                }
+            }
+            Ok(results)
+        }
+
+        /// The off-chain environment cannot call the DAO itself, so the batch is dispatched
+        /// directly and the contract struct is restored from a snapshot when it fails. Changes
+        /// written to mappings cannot be reverted that way; a failing batch that wrote any
+        /// panics instead.
+        #[cfg(test)]
+        fn dispatch_atomic(
+            &mut self,
+            calls: Vec<Call>,
+            results: &mut Vec<CallResult>,
+        ) -> Result<bool, Error> {
+            use ink::storage::traits::Storable;
+
+            let account = self.env().account_id();
+            let (_, writes) = ink::env::test::get_contract_storage_rw::<Environment>(account);
+            let mut snapshot = Vec::new();
+            Storable::encode(self, &mut snapshot);
+            match self.dispatch_all(calls) {
+                Ok(batch_results) => {
+                    results.extend(batch_results);
+                    Ok(true)
+                }
+                Err(_) => {
+                    assert_eq!(
+                        ink::env::test::get_contract_storage_rw::<Environment>(account).1,
+                        writes,
+                        "the failed atomic batch wrote to storage, which is only reverted on chain"
+                    );
+                    *self = <Self as Storable>::decode(&mut &snapshot[..])
+                        .expect("The snapshot is encoded above.");
+                    results.push(CallResult::Failed);
+                    Ok(false)
+                }
+            }
+        }
+        /// Dispatches an atomic batch through `dispatch_atomic_batch` in a call of the DAO to
+        /// itself, whose effects are reverted as a whole when it fails.
+        #[cfg(not(test))]
+        fn dispatch_atomic(
+            &mut self,
+            calls: Vec<Call>,
+            results: &mut Vec<CallResult>,
+        ) -> Result<bool, Error> {
+            // the nested call works on the stored state, so pending changes are written before
+            // and its changes are loaded after the call
+            let key = <Self as ink::storage::traits::StorageKey>::KEY;
+            ink::env::set_contract_storage(&key, self);
+            let result = build_call::<<Self as ::ink::env::ContractEnv>::Env>()
+                .call(self.env().account_id())
+                .call_flags(CallFlags::ALLOW_REENTRY)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "dispatch_atomic_batch"
+                    )))
+                    .push_arg(&calls),
+                )
+                .returns::<Result<Vec<CallResult>, Error>>()
+                .try_invoke();
+            *self = ink::env::get_contract_storage(&key)
+                .ok()
+                .flatten()
+                .expect("The contract storage is written above.");
+
+            match result {
+                Ok(Ok(Ok(batch_results))) => {
+                    results.extend(batch_results);
+                    Ok(true)
+                }
+                _ => {
+                    results.push(CallResult::Failed);
+                    Ok(false)
+                }
+            }
+        }
+
+        #[cfg(test)]
+        fn dispatch_contract_call(&self, _call: ContractCall) -> bool {
+            true
+        }
+        #[cfg(not(test))]
+        fn dispatch_contract_call(&self, call: ContractCall) -> bool {
+            // source: https://github.com/use-ink/ink-examples/blob/main/multisig/lib.rs#L541
+            let call_flags = if call.allow_reentry {
+                CallFlags::ALLOW_REENTRY
+            } else {
+                CallFlags::empty()
+            };
+
+            let result = build_call::<<Self as ::ink::env::ContractEnv>::Env>()
+                .call(call.callee)
+                .ref_time_limit(call.ref_time_limit)
+                .transferred_value(call.transferred_value)
+                .call_flags(call_flags)
+                .exec_input(
+                    ExecutionInput::new(call.selector.into()).push_arg(CallInput(&call.input)),
+                )
+                .returns::<CallOutput>()
+                .try_invoke();
+            result.is_ok()
+        }
+
+        #[cfg(test)]
+        fn dispatch_chain_call(&self, _call: ChainCall) -> bool {
+            true
+        }
+        #[cfg(not(test))]
+        fn dispatch_chain_call(&self, call: ChainCall) -> bool {
+            let dest = call.get_dest();
+            let msg = call.get_msg();
+
+            // TODO: proper error handling
+            // use xcm_execute if dest is local chain, otherwise xcm_send
+            if dest == Location::here() {
+                self.env().xcm_execute(&VersionedXcm::V4(msg)).is_ok()
+            } else {
+                self.env()
+                    .xcm_send(&VersionedLocation::V4(dest), &VersionedXcm::V4(msg))
+                    .is_ok()
+            }
+        }
+
+        #[cfg(test)]
+        fn dispatch_runtime_call(&self, _call: Vec<u8>) -> bool {
+            true
+        }
+        #[cfg(not(test))]
+        fn dispatch_runtime_call(&self, call: Vec<u8>) -> bool {
+            self.env().call_runtime(&CallInput(&call)).is_ok()
+        }
+
+        /// The off-chain environment cannot instantiate contracts. The address is derived from
+        /// the instantiation like pallet-contracts does, and the instantiation fails if the DAO
+        /// cannot pay the endowment.
+        #[cfg(test)]
+        fn instantiate(&mut self, call: InstantiateCall) -> Option<AccountId> {
+            if call.endowment > self.env().balance() {
+                return None;
+            }
+            let mut input = call.selector.to_vec();
+            input.extend(call.input);
+            let mut address = [0u8; 32];
+            ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(
+                &(
+                    b"contract_addr_v1",
+                    self.env().account_id(),
+                    call.code_hash,
+                    input,
+                    call.salt,
+                ),
+                &mut address,
+            );
+            Some(AccountId::from(address))
+        }
+        /// Instantiates a contract, returning its address unless the instantiation failed.
+        #[cfg(not(test))]
+        fn instantiate(&mut self, call: InstantiateCall) -> Option<AccountId> {
+            build_create::<InstantiatedContract>()
+                .code_hash(call.code_hash)
+                .ref_time_limit(call.ref_time_limit)
+                .proof_size_limit(call.proof_size_limit)
+                .endowment(call.endowment)
+                .exec_input(
+                    ExecutionInput::new(call.selector.into()).push_arg(CallInput(&call.input)),
+                )
+                .salt_bytes(&call.salt)
+                .returns::<InstantiatedContract>()
+                .try_instantiate()
+                .ok()?
+                .ok()
+                .map(|contract| contract.0)
+        }
+
+        fn dispatch_internal(&mut self, call: InternalCall) -> Result<bool, Error> {
+            match call {
+                InternalCall::AddMember(account) => {
+                    if self.members.contains(&account) {
+                        return Err(Error::AlreadyMember);
+                    }
+                    self.members.push(account);
+                    self.applications.remove(account);
+                    self.env().emit_event(MemberRegistered { member: account });
+                }
+                InternalCall::RemoveMember(account) => {
+                    if !self.members.contains(&account) {
+                        return Err(Error::NotMember);
+                    }
+                    self.remove_member(account);
+                    // the deposit is forfeited to the DAO
+                    if let Some(deposit) = self.deposits.take(account) {
+                        self.reserved = self.reserved.saturating_sub(deposit);
+                    }
+                    self.env()
+                        .emit_event(MemberDeregistered { member: account });
+                }
+                InternalCall::AllowCodeHash(code_hash) => {
+                    if !self.allowed_code_hashes.contains(&code_hash) {
+                        self.allowed_code_hashes.push(code_hash);
+                    }
+                }
+                InternalCall::DisallowCodeHash(code_hash) => {
+                    self.allowed_code_hashes.retain(|x| x != &code_hash);
+                }
+                InternalCall::SetCodeHash(code_hash) => {
+                    self.env()
+                        .set_code_hash(&code_hash)
+                        .map_err(|_| Error::DispatchFailed)?;
+                }
+            }
+            Ok(true)
+        }
+
+        /// Stores a new proposal on behalf of the caller, without checking its membership.
+        fn submit_proposal(&mut self, call: Call, metadata: ProposalMetadata) -> u32 {
+            let id = self.next_id;
+            let proposal = Proposal {
+                proposer: self.env().caller(),
+                call,
+                metadata,
+                voting_period_end: self.env().block_number().saturating_add(self.voting_period),
+                electorate_size: self.members.len() as u32,
+            };
+
+            self.proposals.insert(id, &proposal);
+            self.electorates.insert(id, &self.members);
+            let delegations = self.delegations_of(&self.members);
+            if !delegations.is_empty() {
+                self.delegation_snapshots.insert(id, &delegations);
+            }
+            self.statuses.insert(id, &ProposalStatus::Active);
+            self.active_proposals.push(id);
+            self.next_id = id.saturating_add(1);
+
+            self.env().emit_event(ProposalCreated {
+                proposal_id: id,
+                proposer: proposal.proposer,
+                call: proposal.call,
+                voting_period_end: proposal.voting_period_end,
+            });
+            id
+        }
+
+        fn ensure_member(&self) -> Result<(), Error> {
+            if !self.is_member() {
+                return Err(Error::NotMember);
+            }
+            Ok(())
+        }
+
+        /// Checks the caller against the membership policy.
+        fn ensure_eligible(&self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            match self.membership_policy {
+                MembershipPolicy::Any => Ok(()),
+                MembershipPolicy::ContractsOnly => {
+                    if !self.env().is_contract(&caller) {
+                        return Err(Error::NotContract);
+                    }
+                    Ok(())
+                }
+                MembershipPolicy::AllowedCodeHashes => {
+                    if !self.env().is_contract(&caller) {
+                        return Err(Error::NotContract);
+                    }
+                    let code_hash = self
+                        .env()
+                        .code_hash(&caller)
+                        .map_err(|_| Error::NotContract)?;
+                    if !self.allowed_code_hashes.contains(&code_hash) {
+                        return Err(Error::CodeHashNotAllowed);
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        /// Checks that every `InternalCall::SetCodeHash` of a call is directly followed by a call
+        /// of the DAO's `migrate` message in the same atomic batch, so that no other message
+        /// reaches the upgraded code before its storage is migrated.
+        fn ensure_migrates_after_upgrade(&self, call: &Call) -> Result<(), Error> {
+            match call {
+                Call::Internal(InternalCall::SetCodeHash(_)) => Err(Error::MigrationMissing),
+                Call::Batch { calls, atomic } => {
+                    let mut calls = calls.iter().peekable();
+                    while let Some(call) = calls.next() {
+                        if matches!(call, Call::Internal(InternalCall::SetCodeHash(_))) {
+                            if !atomic || !calls.peek().is_some_and(|next| self.is_migration(next))
+                            {
+                                return Err(Error::MigrationMissing);
+                            }
+                        } else {
+                            self.ensure_migrates_after_upgrade(call)?;
+                        }
+                    }
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Whether a call dispatches the DAO's `migrate` message.
+        fn is_migration(&self, call: &Call) -> bool {
+            matches!(call, Call::Contract(call) if call.callee == self.env().account_id()
+                && call.selector == ink::selector_bytes!("migrate")
+                && call.allow_reentry)
+        }
+
+        fn ensure_proposal_exists(&self, prop_id: u32) -> Result<(), Error> {
+            if !self.proposals.contains(prop_id) {
+                return Err(Error::ProposalNotFound);
+            }
+            Ok(())
+        }
+
+        fn ensure_active(&self, prop_id: u32) -> Result<(), Error> {
+            self.ensure_proposal_exists(prop_id)?;
+            if self.statuses.get(prop_id) != Some(ProposalStatus::Active) {
+                return Err(Error::AlreadyResolved);
+            }
+            Ok(())
+        }
+
+        fn ensure_can_vote(&self, prop_id: u32, voter: AccountId) -> Result<(), Error> {
+            if !self.members.contains(&voter) {
+                return Err(Error::NotMember);
+            }
+            self.ensure_active(prop_id)?;
+            if self.status(prop_id) == Some(ProposalStatus::Expired) {
+                return Err(Error::AlreadyResolved);
+            }
+            self.ensure_in_electorate(prop_id, voter)
+        }
+
+        /// Locks `amount` more of the value of `account` for the lock periods of `conviction`
+        /// after `vote_end`.
+        fn lock(
+            &mut self,
+            account: AccountId,
+            amount: Balance,
+            vote_end: BlockNumber,
+            conviction: Conviction,
+        ) {
+            let until = vote_end
+                .saturating_add(self.voting_period.saturating_mul(conviction.lock_periods()));
+            // an existing lock is topped up and only ever extended
+            let (locked, locked_until) = self.locks.get(account).unwrap_or_default();
+            let lock = (locked.saturating_add(amount), locked_until.max(until));
+            self.locks.insert(account, &lock);
+            self.reserved = self.reserved.saturating_add(amount);
+            self.env().emit_event(Locked {
+                account,
+                amount: lock.0,
+                until: lock.1,
+            });
+        }
+
+        fn is_commit_reveal(&self) -> bool {
+            self.governance_rule.reveal_period > 0
+        }
+
+        /// Votes are cast in the open unless the DAO uses commit-reveal voting.
+        fn ensure_open_voting(&self) -> Result<(), Error> {
+            if self.is_commit_reveal() {
+                return Err(Error::InvalidVotingMode);
+            }
+            Ok(())
+        }
+
+        /// End of the window in which committed votes can be revealed, which is the end of the
+        /// voting period when votes are not committed.
+        fn reveal_period_end(&self, proposal: &Proposal) -> BlockNumber {
+            proposal
+                .voting_period_end
+                .saturating_add(self.governance_rule.reveal_period)
+        }
+
+        fn ensure_in_electorate(&self, prop_id: u32, voter: AccountId) -> Result<(), Error> {
+            let electorate = self.electorates.get(prop_id).unwrap_or_default();
+            if !electorate.contains(&voter) {
+                return Err(Error::NotInElectorate);
+            }
+            Ok(())
+        }
+
+        /// Removes `member` together with its delegation and its votes on active proposals.
+        fn remove_member(&mut self, member: AccountId) {
+            self.members.retain(|&x| x != member);
+            self.delegations.remove(member);
+            self.remove_votes_of(member);
+        }
+
+        /// Withdraws the votes and delegation of `member` from all active proposals.
+        fn remove_votes_of(&mut self, member: AccountId) {
+            for prop_id in self.active_proposals.iter() {
+                if let Some(mut votes) = self.votes.get(prop_id) {
+                    let len = votes.len();
+                    votes.retain(|(voter, _, _)| voter != &member);
+                    if votes.len() != len {
+                        self.votes.insert(prop_id, &votes);
+                    }
+                }
+                if let Some(mut delegations) = self.delegation_snapshots.get(prop_id) {
+                    let len = delegations.len();
+                    delegations.retain(|(delegator, _)| delegator != &member);
+                    if delegations.len() != len {
+                        self.delegation_snapshots.insert(prop_id, &delegations);
+                    }
+                }
+            }
+        }
+
+        /// Checks that `signature` was made by `voter` over the ballot payload.
+        fn verify_ballot(
+            &self,
+            prop_id: u32,
+            voter: &AccountId,
+            vote: &Vote,
+            signature: &Signature,
+        ) -> Result<(), Error> {
+            let nonce = self.ballot_nonces.get(voter).unwrap_or_default();
+            let payload = ballot_payload(&self.env().account_id(), prop_id, vote, nonce);
+            let voter: &[u8; 32] = voter.as_ref();
+
+            match signature {
+                Signature::Sr25519(signature) => {
+                    ink::env::sr25519_verify(signature, &payload, voter)
+                        .map_err(|_| Error::InvalidSignature)
+                }
+                Signature::Ecdsa(signature) => {
+                    let mut message_hash = [0u8; 32];
+                    ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&payload, &mut message_hash);
+                    let mut public_key = [0u8; 33];
+                    ink::env::ecdsa_recover(signature, &message_hash, &mut public_key)
+                        .map_err(|_| Error::InvalidSignature)?;
+                    let mut signer = [0u8; 32];
+                    ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&public_key, &mut signer);
+                    if &signer != voter {
+                        return Err(Error::InvalidSignature);
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        fn find_vote(votes: &[(AccountId, Vote, u128)], voter: &AccountId) -> Option<usize> {
+            votes.iter().position(|(x, _, _)| x == voter)
+        }
+
+        /// Stores or replaces the vote of `voter` on a proposal.
+        fn record_vote(&mut self, prop_id: u32, voter: AccountId, vote: Vote, weight: u128) {
+            let mut votes = self.votes.get(prop_id).unwrap_or_default();
+
+            match Self::find_vote(&votes, &voter) {
+                Some(index) => {
+                    votes[index] = (voter, vote.clone(), weight);
+                    self.env().emit_event(VoteChanged {
+                        proposal_id: prop_id,
+                        voter,
+                        vote,
+                        weight,
+                    });
+                }
+                None => {
+                    votes.push((voter, vote.clone(), weight));
+                    self.env().emit_event(VoteCast {
+                        proposal_id: prop_id,
+                        voter,
+                        vote,
+                        weight,
+                    });
+                }
+            }
+
+            self.votes.insert(prop_id, &votes);
+            self.snapshot_delegated_weights(prop_id, voter);
+        }
+    }
+
+    impl SuperDao for Superdao {
+        #[ink(message, payable)]
+        fn register_member(&mut self) -> Result<(), Error> {
+            if self.is_member() {
+                return Err(Error::AlreadyMember);
+            }
+            if self.membership_mode == MembershipMode::ByVote && !self.members.is_empty() {
+                return Err(Error::InvalidMembershipMode);
+            }
+            self.ensure_eligible()?;
+            let deposit = self.env().transferred_value();
+            if deposit < self.membership_deposit {
+                return Err(Error::InsufficientDeposit);
+            }
+            let caller = self.env().caller();
+            self.members.push(caller);
+            if deposit > 0 {
+                self.deposits.insert(caller, &deposit);
+                self.reserved = self.reserved.saturating_add(deposit);
+            }
+            self.env().emit_event(MemberRegistered { member: caller });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn deregister_member(&mut self) -> Result<(), Error> {
+            self.ensure_member()?;
+            let caller = self.env().caller();
+            self.remove_member(caller);
+
+            if let Some(deposit) = self.deposits.take(caller) {
+                self.reserved = self.reserved.saturating_sub(deposit);
+                self.env()
+                    .transfer(caller, deposit)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+            self.env().emit_event(MemberDeregistered { member: caller });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        fn apply_for_membership(&mut self) -> Result<u32, Error> {
+            // an empty DAO has nobody to vote on the application
+            if self.membership_mode != MembershipMode::ByVote || self.members.is_empty() {
+                return Err(Error::InvalidMembershipMode);
+            }
+            if self.is_member() {
+                return Err(Error::AlreadyMember);
+            }
+            self.ensure_eligible()?;
+            let caller = self.env().caller();
+            if self.applications.contains(caller) {
+                return Err(Error::ApplicationPending);
+            }
+            // the deposit is escrowed, so that every pending application ties up funds
+            let deposit = self.env().transferred_value();
+            if deposit < self.membership_deposit {
+                return Err(Error::InsufficientDeposit);
+            }
+            if deposit > 0 {
+                self.deposits.insert(caller, &deposit);
+                self.reserved = self.reserved.saturating_add(deposit);
+            }
+
+            let id = self.submit_proposal(
+                Call::Internal(InternalCall::AddMember(caller)),
+                ProposalMetadata::default(),
+            );
+            self.applications.insert(caller, &id);
+            self.env().emit_event(MembershipApplied {
+                applicant: caller,
+                proposal_id: id,
+            });
+            Ok(id)
+        }
+
+        #[ink(message)]
+        fn create_proposal(
+            &mut self,
+            call: Call,
+            metadata: ProposalMetadata,
+        ) -> Result<u32, Error> {
+            self.ensure_member()?;
+            metadata.ensure_bounded()?;
+            if call.batch_depth() > MAX_BATCH_DEPTH {
+                return Err(Error::BatchTooDeep);
+            }
+            self.ensure_migrates_after_upgrade(&call)?;
+            Ok(self.submit_proposal(call, metadata))
+        }
+
+        #[ink(message)]
+        fn cancel_proposal(&mut self, prop_id: u32) -> Result<(), Error> {
+            self.ensure_active(prop_id)?;
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            if proposal.proposer != self.env().caller() {
+                return Err(Error::NotProposer);
+            }
+            if self.env().block_number() >= proposal.voting_period_end {
+                return Err(Error::VotingPeriodEnded);
+            }
+
+            self.active_proposals.retain(|&x| x != prop_id);
+            self.statuses.insert(prop_id, &ProposalStatus::Cancelled);
+            self.refund_application(prop_id)?;
+            self.env().emit_event(ProposalCancelled {
+                proposal_id: prop_id,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn veto_proposal(&mut self, prop_id: u32) -> Result<(), Error> {
+            let threshold = self.governance_rule.veto.ok_or(Error::VetoDisabled)?;
+            let caller = self.env().caller();
+            self.ensure_member()?;
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            match self.status(prop_id) {
+                Some(ProposalStatus::Active) | Some(ProposalStatus::Approved) => {}
+                _ => return Err(Error::AlreadyResolved),
+            }
+            self.ensure_in_electorate(prop_id, caller)?;
+
+            let mut vetoes = self.vetoes.get(prop_id).unwrap_or_default();
+            if vetoes.contains(&caller) {
+                return Err(Error::AlreadyVetoed);
+            }
+            vetoes.push(caller);
+            self.vetoes.insert(prop_id, &vetoes);
+            self.env().emit_event(VetoCast {
+                proposal_id: prop_id,
+                voter: caller,
+            });
+
+            if threshold.is_reached_by(vetoes.len() as u128, proposal.electorate_size as u128) {
+                self.active_proposals.retain(|&x| x != prop_id);
+                self.enactments.remove(prop_id);
+                self.statuses.insert(prop_id, &ProposalStatus::Vetoed);
+                self.refund_application(prop_id)?;
+                self.env().emit_event(ProposalVetoed {
+                    proposal_id: prop_id,
+                });
+            }
+            Ok(())
+        }
+
+        // TODO: vote enum type!
+        #[ink(message)]
+        fn vote(&mut self, prop_id: u32, vote: Vote) -> Result<(), Error> {
+            self.ensure_open_voting()?;
+            self.ensure_can_vote(prop_id, self.env().caller())?;
+
+            let caller = self.env().caller();
+            let weight = self.voting_weight(caller)?;
+            self.record_vote(prop_id, caller, vote, weight);
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        fn vote_with_conviction(
+            &mut self,
+            prop_id: u32,
+            vote: Vote,
+            conviction: Conviction,
+        ) -> Result<(), Error> {
+            self.ensure_open_voting()?;
+            // the conviction multiplies the locked value, which is the weight in no other mode
+            if !self.is_lock_weighted() {
+                return Err(Error::InvalidVotingMode);
+            }
+            self.ensure_can_vote(prop_id, self.env().caller())?;
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::NothingLocked);
+            }
+
+            let caller = self.env().caller();
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            self.lock(caller, amount, proposal.voting_period_end, conviction);
+            self.record_vote(prop_id, caller, vote, conviction.weight(amount));
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn vote_with_credits(
+            &mut self,
+            prop_id: u32,
+            vote: Vote,
+            credits: u128,
+        ) -> Result<(), Error> {
+            self.ensure_open_voting()?;
+            self.ensure_can_vote(prop_id, self.env().caller())?;
+            let VotingMode::Quadratic {
+                credits: allowance,
+                epoch,
+            } = self.voting_mode
+            else {
+                return Err(Error::InvalidVotingMode);
+            };
+
+            let caller = self.env().caller();
+            let period = self.credit_period(prop_id, epoch)?;
+            // credits spent on an earlier vote on this proposal are given back first
+            let previous = self
+                .spent_credits
+                .get((prop_id, caller))
+                .unwrap_or_default();
+            let used = self
+                .used_credits
+                .get((caller, period))
+                .unwrap_or_default()
+                .saturating_sub(previous)
+                .saturating_add(credits);
+            if credits == 0 || used > allowance {
+                return Err(Error::InsufficientCredits);
+            }
+
+            self.used_credits.insert((caller, period), &used);
+            self.spent_credits.insert((prop_id, caller), &credits);
+            self.record_vote(prop_id, caller, vote, quadratic_weight(credits));
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        fn commit_vote(&mut self, prop_id: u32, commitment: [u8; 32]) -> Result<(), Error> {
+            if !self.is_commit_reveal() {
+                return Err(Error::InvalidVotingMode);
+            }
+            self.ensure_can_vote(prop_id, self.env().caller())?;
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            if self.env().block_number() >= proposal.voting_period_end {
+                return Err(Error::VotingPeriodEnded);
+            }
+
+            let caller = self.env().caller();
+            // the value locked with an earlier commitment stays staked on the proposal
+            let (_, staked) = self.commitments.get((prop_id, caller)).unwrap_or_default();
+            let amount = self.env().transferred_value();
+            if amount > 0 {
+                if !self.is_lock_weighted() {
+                    return Err(Error::InvalidVotingMode);
+                }
+                let reveal_period_end = self.reveal_period_end(&proposal);
+                self.lock(caller, amount, reveal_period_end, Conviction::None);
+            }
+            self.commitments.insert(
+                (prop_id, caller),
+                &(commitment, staked.saturating_add(amount)),
+            );
+            self.env().emit_event(VoteCommitted {
+                proposal_id: prop_id,
+                voter: caller,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn reveal_vote(
+            &mut self,
+            prop_id: u32,
+            vote: Vote,
+            conviction: Conviction,
+            salt: [u8; 32],
+        ) -> Result<(), Error> {
+            if !self.is_commit_reveal() {
+                return Err(Error::InvalidVotingMode);
+            }
+            self.ensure_can_vote(prop_id, self.env().caller())?;
+            let proposal = self.proposals.get(prop_id).ok_or(Error::ProposalNotFound)?;
+            let now = self.env().block_number();
+            if now < proposal.voting_period_end {
+                return Err(Error::VotingPeriodNotEnded);
+            }
+            let reveal_period_end = self.reveal_period_end(&proposal);
+            if now >= reveal_period_end {
+                return Err(Error::RevealPeriodEnded);
+            }
+
+            let caller = self.env().caller();
+            let (commitment, staked) = self
+                .commitments
+                .get((prop_id, caller))
+                .ok_or(Error::NoCommitment)?;
+            if commitment != vote_commitment(prop_id, &caller, &vote, conviction, &salt) {
+                return Err(Error::InvalidReveal);
+            }
+
+            let weight = if self.is_lock_weighted() {
+                conviction.weight(staked)
+            } else if conviction != Conviction::None {
+                return Err(Error::InvalidVotingMode);
+            } else {
+                self.voting_weight(caller)?
+            };
+            if staked > 0 && conviction != Conviction::None {
+                self.lock(caller, 0, reveal_period_end, conviction);
+            }
+            self.commitments.remove((prop_id, caller));
+            self.record_vote(prop_id, caller, vote, weight);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn submit_signed_votes(
+            &mut self,
+            prop_id: u32,
+            ballots: Vec<(AccountId, Vote, Signature)>,
+        ) -> Result<(), Error> {
+            self.ensure_open_voting()?;
+            for (voter, vote, signature) in ballots {
+                self.ensure_can_vote(prop_id, voter)?;
+                self.verify_ballot(prop_id, &voter, &vote, &signature)?;
+
+                let nonce = self.ballot_nonces.get(voter).unwrap_or_default();
+                self.ballot_nonces.insert(voter, &nonce.saturating_add(1));
+                let weight = self.voting_weight(voter)?;
+                self.record_vote(prop_id, voter, vote, weight);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn unlock(&mut self) -> Result<Balance, Error> {
+            let caller = self.env().caller();
+            let (amount, until) = self.locks.get(caller).ok_or(Error::NothingLocked)?;
+            if self.env().block_number() < until {
+                return Err(Error::LockNotExpired);
+            }
+
+            self.locks.remove(caller);
+            self.reserved = self.reserved.saturating_sub(amount);
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::TransferFailed)?;
+            self.env().emit_event(Unlocked {
+                account: caller,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        #[ink(message)]
+        fn delegate(&mut self, to: AccountId) -> Result<(), Error> {
+            self.ensure_member()?;
+            // quadratic and locked votes are paid for with each member's own credits or value
+            if matches!(
+                self.voting_mode,
+                VotingMode::Quadratic { .. } | VotingMode::LockWeighted
+            ) {
+                return Err(Error::InvalidVotingMode);
+            }
+            if !self.members.contains(&to) {
+                return Err(Error::NotMember);
+            }
+
+            let caller = self.env().caller();
+            let mut current = to;
+            for _ in 0..self.members.len() {
+                if current == caller {
+                    return Err(Error::DelegationCycle);
+                }
+                match self.delegations.get(current) {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+
+            self.delegations.insert(caller, &to);
+            self.env().emit_event(Delegated {
+                delegator: caller,
+                delegate: to,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn undelegate(&mut self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if self.delegations.take(caller).is_none() {
+                return Err(Error::NotDelegating);
+            }
+            self.env().emit_event(Undelegated { delegator: caller });
+            Ok(())
+        }
+    }
+
+    impl SuperDaoQuery for Superdao {
+        #[ink(message)]
+        fn get_members(&self) -> Vec<AccountId> {
+            self.members.clone()
+        }
+
+        #[ink(message)]
+        fn is_member(&self) -> bool {
+            self.members.contains(&self.env().caller())
+        }
+
+        #[ink(message)]
+        fn get_proposal(&self, index: u32) -> Option<Proposal> {
+            self.proposals.get(index)
+        }
+
+        #[ink(message)]
+        fn get_proposals(&self) -> Vec<(u32, Proposal)> {
+            self.active_proposals
+                .iter()
+                .map(|&x| {
+                    (
+                        x,
+                        self.proposals
+                            .get(x)
+                            .expect("If prop_id is present, proposal exists."),
+                    )
+                })
+                // expired proposals are only dropped once someone tries to resolve them
+                .filter(|(x, proposal)| !self.is_expired(*x, proposal))
+                .collect()
+        }
+
+        #[ink(message)]
+        fn get_proposal_status(&self, proposal_id: u32) -> Option<ProposalStatus> {
+            self.status(proposal_id)
+        }
+
+        #[ink(message)]
+        fn get_enactment_block(&self, proposal_id: u32) -> Option<BlockNumber> {
+            self.enactments.get(proposal_id)
+        }
+
+        #[ink(message)]
+        fn get_execution_results(&self, proposal_id: u32) -> Vec<CallResult> {
+            self.execution_results.get(proposal_id).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn get_votes(&self, proposal_id: u32) -> Vec<(AccountId, Vote)> {
+            self.votes
+                .get(proposal_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(voter, vote, _)| (voter, vote))
+                .collect()
+        }
+
+        #[ink(message)]
+        fn get_electorate(&self, proposal_id: u32) -> Vec<AccountId> {
+            self.electorates.get(proposal_id).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn get_tally(&self, proposal_id: u32) -> Tally {
+            Self::tally(&self.counted_votes(proposal_id))
+        }
+
+        #[ink(message)]
+        fn get_delegation(&self, member: AccountId) -> Option<AccountId> {
+            self.delegations.get(member)
+        }
+
+        #[ink(message)]
+        fn get_remaining_credits(&self, member: AccountId, proposal_id: u32) -> u128 {
+            let VotingMode::Quadratic { credits, epoch } = self.voting_mode else {
+                return 0;
+            };
+            let Ok(period) = self.credit_period(proposal_id, epoch) else {
+                return 0;
+            };
+            credits.saturating_sub(self.used_credits.get((member, period)).unwrap_or_default())
+        }
+
+        #[ink(message)]
+        fn get_ballot_nonce(&self, member: AccountId) -> u64 {
+            self.ballot_nonces.get(member).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn get_lock(&self, account: AccountId) -> Option<(Balance, BlockNumber)> {
+            self.locks.get(account)
+        }
+
+        #[ink(message)]
+        fn get_vetoes(&self, proposal_id: u32) -> Vec<AccountId> {
+            self.vetoes.get(proposal_id).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn get_vote_threshold(&self) -> u8 {
+            self.vote_threshold
+        }
+
+        #[ink(message)]
+        fn get_governance_rule(&self) -> GovernanceRule {
+            self.governance_rule
+        }
+
+        #[ink(message)]
+        fn get_voting_mode(&self) -> VotingMode {
+            self.voting_mode
+        }
+
+        #[ink(message)]
+        fn get_voting_period(&self) -> BlockNumber {
+            self.voting_period
+        }
+
+        #[ink(message)]
+        fn get_membership_deposit(&self) -> Balance {
+            self.membership_deposit
+        }
+
+        #[ink(message)]
+        fn get_membership_mode(&self) -> MembershipMode {
+            self.membership_mode
+        }
+
+        #[ink(message)]
+        fn get_membership_policy(&self) -> MembershipPolicy {
+            self.membership_policy
+        }
+
+        #[ink(message)]
+        fn get_allowed_code_hashes(&self) -> Vec<Hash> {
+            self.allowed_code_hashes.clone()
+        }
+
+        #[ink(message)]
+        fn get_active_proposals(&self) -> Vec<u32> {
+            self.get_proposals().into_iter().map(|(x, _)| x).collect()
+        }
+
+        #[ink(message)]
+        fn get_storage_version(&self) -> u32 {
+            ink::env::get_contract_storage(&STORAGE_VERSION_KEY)
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn new_works() {
+            let superdao = Superdao::new(
+                5,
+                4,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            assert_eq!(superdao.members.len(), 0);
+            assert_eq!(superdao.active_proposals.len(), 0);
+            assert_eq!(superdao.next_id, 0);
+            assert_eq!(superdao.vote_threshold, 5);
+            assert_eq!(superdao.voting_period, 4);
+        }
+
+        #[ink::test]
+        fn new_rejects_quadratic_commit_reveal_voting() {
+            let rule = GovernanceRule {
+                reveal_period: 5,
+                ..Default::default()
+            };
+            let superdao = Superdao::new(
+                0,
+                10,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::Quadratic {
+                    credits: 100,
+                    epoch: 0,
+                },
+            );
+            assert_eq!(superdao.err(), Some(Error::InvalidVotingMode));
+        }
+
+        #[ink::test]
+        fn default_works() {
+            let superdao = Superdao::default();
+            assert_eq!(superdao.members.len(), 0);
+            assert_eq!(superdao.active_proposals.len(), 0);
+            assert_eq!(superdao.next_id, 0);
+            assert_eq!(superdao.vote_threshold, 0);
+            assert_eq!(superdao.governance_rule, GovernanceRule::default());
+            assert_eq!(superdao.voting_period, 0);
+        }
+
+        #[ink::test]
+        fn migrate_sets_storage_version() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            assert_eq!(superdao.get_storage_version(), STORAGE_VERSION);
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![],
+                        atomic: false,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+
+            // storage left behind by a version that did not record its layout
+            ink::env::clear_contract_storage(&STORAGE_VERSION_KEY);
+            assert_eq!(superdao.get_storage_version(), 0);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(superdao.migrate(), Err(Error::NotSelf));
+
+            // the DAO calling itself
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(ink::env::test::callee::<
+                Environment,
+            >());
+            assert!(superdao.migrate().is_ok());
+            assert_eq!(superdao.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(superdao.get_tally(proposal_id).ayes, 1);
+        }
+
+        #[ink::test]
+        fn create_proposal_requires_migration_after_upgrade() {
+            let mut superdao = Superdao::default();
+            let code_hash = Hash::from([7; 32]);
+            let upgrade = || Call::Internal(InternalCall::SetCodeHash(code_hash));
+            let migrate = || {
+                Call::Contract(ContractCall {
+                    callee: ink::env::test::callee::<Environment>(),
+                    selector: ink::selector_bytes!("migrate"),
+                    input: vec![],
+                    transferred_value: 0,
+                    ref_time_limit: 0,
+                    allow_reentry: true,
+                })
+            };
+
+            assert!(superdao.register_member().is_ok());
+            for call in [
+                upgrade(),
+                Call::Batch {
+                    calls: vec![upgrade()],
+                    atomic: true,
+                },
+                Call::Batch {
+                    calls: vec![upgrade(), migrate()],
+                    atomic: false,
+                },
+                Call::Batch {
+                    calls: vec![migrate(), upgrade()],
+                    atomic: true,
+                },
+                Call::Batch {
+                    calls: vec![
+                        upgrade(),
+                        Call::Batch {
+                            calls: vec![migrate()],
+                            atomic: true,
+                        },
+                    ],
+                    atomic: true,
+                },
+            ] {
+                assert_eq!(
+                    superdao.create_proposal(call, ProposalMetadata::default()),
+                    Err(Error::MigrationMissing)
+                );
+            }
+
+            let upgrade_and_migrate = Call::Batch {
+                calls: vec![upgrade(), migrate()],
+                atomic: true,
+            };
+            assert!(superdao
+                .create_proposal(upgrade_and_migrate.clone(), ProposalMetadata::default())
+                .is_ok());
+            // a nested upgrade is checked as well
+            assert!(superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![upgrade_and_migrate],
+                        atomic: false,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn register_member_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            assert_eq!(superdao.members.len(), 2);
+        }
+
+        #[ink::test]
+        fn deregister_member_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+
+            assert!(superdao.deregister_member().is_ok());
+            assert_eq!(superdao.members.len(), 1);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.deregister_member().is_ok());
+            assert_eq!(superdao.members.len(), 0);
+        }
+
+        #[ink::test]
+        fn register_member_requires_deposit() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                100,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ink::env::test::callee::<Environment>();
+
+            // bob is not the contract account, so the refund is an actual transfer
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            ink::env::test::set_value_transferred::<Environment>(99);
+            assert_eq!(superdao.register_member(), Err(Error::InsufficientDeposit));
+
+            ink::env::test::set_value_transferred::<Environment>(150);
+            assert!(superdao.register_member().is_ok());
+            assert_eq!(superdao.deposits.get(accounts.bob), Some(150));
+
+            // the deposit is refunded in full on deregistration
+            ink::env::test::set_value_transferred::<Environment>(0);
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            let balance = ink::env::test::get_account_balance::<Environment>(accounts.bob)
+                .expect("bob has a balance");
+            assert!(superdao.deregister_member().is_ok());
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(accounts.bob),
+                Ok(balance + 150)
+            );
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(contract),
+                Ok(850)
+            );
+            assert_eq!(superdao.deposits.get(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn proposals_cannot_spend_reserved_balance() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                100,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ink::env::test::callee::<Environment>();
+            let transfer = |value: Balance| {
+                Call::Contract(ContractCall {
+                    callee: accounts.bob,
+                    selector: [0; 4],
+                    input: vec![],
+                    transferred_value: value,
+                    ref_time_limit: 0,
+                    allow_reentry: false,
+                })
+            };
+
+            ink::env::test::set_value_transferred::<Environment>(100);
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_value_transferred::<Environment>(0);
+            // the DAO holds 50 of its own next to the deposit
+            ink::env::test::set_account_balance::<Environment>(contract, 150);
+
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![transfer(51), transfer(50)],
+                        atomic: false,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: false })
+            );
+            assert_eq!(
+                superdao.get_execution_results(proposal_id),
+                vec![CallResult::Failed, CallResult::Dispatched { success: true }]
+            );
+
+            // what a runtime call spends is checked after its dispatch
+            let proposal_id = superdao
+                .create_proposal(Call::Runtime(vec![0, 0, 0]), ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            ink::env::test::set_account_balance::<Environment>(contract, 99);
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Err(Error::SpendsReservedBalance)
+            );
+        }
+
+        #[ink::test]
+        fn remove_member_forfeits_deposit() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                100,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ink::env::test::callee::<Environment>();
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+
+            ink::env::test::set_value_transferred::<Environment>(100);
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_value_transferred::<Environment>(0);
+
+            ink::env::test::set_caller::<Environment>(accounts.alice);
+            for call in [
+                InternalCall::RemoveMember(accounts.bob),
+                InternalCall::AddMember(accounts.bob),
+            ] {
+                let proposal_id = superdao
+                    .create_proposal(Call::Internal(call), ProposalMetadata::default())
+                    .unwrap();
+                assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+                assert_eq!(
+                    superdao.resolve_proposal(proposal_id),
+                    Ok(ProposalStatus::Executed { success: true })
+                );
+            }
+            assert_eq!(superdao.deposits.get(accounts.bob), None);
+
+            // readmitted by vote without a deposit, bob has nothing to be refunded
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            let balance = ink::env::test::get_account_balance::<Environment>(accounts.bob)
+                .expect("bob has a balance");
+            assert!(superdao.deregister_member().is_ok());
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(accounts.bob),
+                Ok(balance)
+            );
+        }
+
+        #[ink::test]
+        fn register_member_checks_policy() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::ContractsOnly,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            assert_eq!(superdao.register_member(), Err(Error::NotContract));
+
+            superdao.membership_policy = MembershipPolicy::AllowedCodeHashes;
+            assert_eq!(superdao.register_member(), Err(Error::NotContract));
+            assert!(superdao.members.is_empty());
+        }
+
+        #[ink::test]
+        fn code_hash_allowlist_is_managed_by_proposal() {
+            let mut superdao = Superdao::default();
+            let code_hash = Hash::from([1; 32]);
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Internal(InternalCall::AllowCodeHash(code_hash)),
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert!(superdao.resolve_proposal(proposal_id).is_ok());
+            assert_eq!(superdao.get_allowed_code_hashes(), vec![code_hash]);
+
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Internal(InternalCall::DisallowCodeHash(code_hash)),
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert!(superdao.resolve_proposal(proposal_id).is_ok());
+            assert!(superdao.get_allowed_code_hashes().is_empty());
+        }
+
+        #[ink::test]
+        fn batch_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let code_hash = Hash::from([1; 32]);
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![
+                            Call::Internal(InternalCall::AddMember(accounts.bob)),
+                            Call::Internal(InternalCall::AddMember(accounts.bob)),
+                            Call::Internal(InternalCall::AllowCodeHash(code_hash)),
+                        ],
+                        atomic: false,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: false })
+            );
+            // the failing call does not prevent the next one
+            assert_eq!(
+                superdao.get_execution_results(proposal_id),
+                vec![
+                    CallResult::Dispatched { success: true },
+                    CallResult::Failed,
+                    CallResult::Dispatched { success: true },
+                ]
+            );
+            assert_eq!(superdao.get_members(), vec![accounts.alice, accounts.bob]);
+            assert_eq!(superdao.get_allowed_code_hashes(), vec![code_hash]);
+        }
+
+        #[ink::test]
+        fn atomic_batch_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![
+                            Call::Internal(InternalCall::AddMember(accounts.bob)),
+                            Call::Internal(InternalCall::AddMember(accounts.charlie)),
+                        ],
+                        atomic: true,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+            assert_eq!(
+                superdao.get_execution_results(proposal_id),
+                vec![CallResult::Dispatched { success: true }; 2]
+            );
+
+            // off-chain, only changes to the contract struct can be reverted, see
+            // `e2e_tests::atomic_batch_reverts_storage` for changes written to mappings
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![
+                            Call::Internal(InternalCall::AllowCodeHash(Hash::from([7; 32]))),
+                            Call::Internal(InternalCall::RemoveMember(accounts.django)),
+                            Call::Internal(InternalCall::RemoveMember(accounts.charlie)),
+                        ],
+                        atomic: true,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: false })
+            );
+            // the batch stops at its first failing call and is reported as a whole
+            assert_eq!(
+                superdao.get_execution_results(proposal_id),
+                vec![CallResult::Failed]
+            );
+            // the code hash allowed before the failing call is reverted
+            assert!(superdao.get_allowed_code_hashes().is_empty());
+            assert_eq!(
+                superdao.get_members(),
+                vec![accounts.alice, accounts.bob, accounts.charlie]
+            );
+        }
+
+        #[ink::test]
+        fn create_proposal_limits_batch_depth() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            let mut call = Call::Internal(InternalCall::AddMember(accounts.bob));
+            for _ in 0..MAX_BATCH_DEPTH {
+                call = Call::Batch {
+                    calls: vec![call],
+                    atomic: false,
+                };
+            }
+            assert!(superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .is_ok());
+
+            let call = Call::Batch {
+                calls: vec![call],
+                atomic: true,
+            };
+            assert_eq!(
+                superdao.create_proposal(call, ProposalMetadata::default()),
+                Err(Error::BatchTooDeep)
+            );
+        }
+
+        #[ink::test]
+        fn dispatch_atomic_batch_requires_self_call() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+
+            assert_eq!(superdao.dispatch_atomic_batch(vec![]), Err(Error::NotSelf));
+        }
+
+        #[ink::test]
+        fn apply_for_membership_works() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                100,
+                MembershipMode::ByVote,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            ink::env::test::set_value_transferred::<Environment>(100);
+
+            // the first member bootstraps the DAO
+            assert_eq!(
+                superdao.apply_for_membership(),
+                Err(Error::InvalidMembershipMode)
+            );
+            assert!(superdao.register_member().is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                superdao.register_member(),
+                Err(Error::InvalidMembershipMode)
+            );
+            ink::env::test::set_value_transferred::<Environment>(99);
+            assert_eq!(
+                superdao.apply_for_membership(),
+                Err(Error::InsufficientDeposit)
+            );
+            ink::env::test::set_value_transferred::<Environment>(100);
+            let proposal_id = superdao.apply_for_membership().unwrap();
+            assert_eq!(
+                superdao.apply_for_membership(),
+                Err(Error::ApplicationPending)
+            );
+            assert_eq!(
+                superdao.get_proposal(proposal_id).unwrap().call,
+                Call::Internal(InternalCall::AddMember(accounts.bob))
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+            assert_eq!(superdao.get_members(), vec![accounts.alice, accounts.bob]);
+            assert_eq!(superdao.applications.get(accounts.bob), None);
+            // the escrowed deposit is kept as membership deposit
+            assert_eq!(superdao.deposits.get(accounts.bob), Some(100));
+        }
+
+        #[ink::test]
+        fn failed_application_refunds_deposit() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                100,
+                MembershipMode::ByVote,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ink::env::test::callee::<Environment>();
+            ink::env::test::set_value_transferred::<Environment>(100);
+            assert!(superdao.register_member().is_ok());
+
+            ink::env::test::set_caller::<Environment>(accounts.bob);
+            let proposal_id = superdao.apply_for_membership().unwrap();
+            ink::env::test::set_value_transferred::<Environment>(0);
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            ink::env::test::set_account_balance::<Environment>(accounts.bob, 0);
+
+            // nobody voted on the application
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Rejected)
+            );
+            assert_eq!(
+                ink::env::test::get_account_balance::<Environment>(accounts.bob),
+                Ok(100)
+            );
+            assert_eq!(superdao.deposits.get(accounts.bob), None);
+            assert_eq!(superdao.applications.get(accounts.bob), None);
+            assert!(!superdao.get_members().contains(&accounts.bob));
+
+            // the applicant can apply again
+            ink::env::test::set_value_transferred::<Environment>(100);
+            assert!(superdao.apply_for_membership().is_ok());
+        }
+
+        #[ink::test]
+        fn apply_for_membership_fails_in_open_mode() {
+            let mut superdao = Superdao::default();
+            assert_eq!(
+                superdao.apply_for_membership(),
+                Err(Error::InvalidMembershipMode)
+            );
+        }
+
+        #[ink::test]
+        fn remove_member_by_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Internal(InternalCall::RemoveMember(accounts.bob)),
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+            assert_eq!(superdao.get_members(), vec![accounts.alice]);
+
+            // removing a non-member fails on dispatch
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Internal(InternalCall::RemoveMember(accounts.bob)),
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Failed)
+            );
+            let event = ink::env::test::recorded_events().last().unwrap();
+            let dispatched = CallDispatched::decode(&mut &event.data[..]).unwrap();
+            assert_eq!(dispatched.proposal_id, proposal_id);
+            assert!(!dispatched.success);
+        }
+
+        #[ink::test]
+        fn create_contract_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            assert!(superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .is_ok());
+            assert_eq!(
+                superdao.proposals.get(superdao.next_id - 1),
+                Some(Proposal {
+                    proposer: accounts.alice,
+                    call,
+                    metadata: ProposalMetadata::default(),
+                    voting_period_end: 0,
+                    electorate_size: 1,
+                })
+            );
+            assert_eq!(superdao.active_proposals.len(), 1);
+        }
+
+        #[ink::test]
+        fn create_proposal_stores_metadata() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let metadata = ProposalMetadata {
+                title: "Fund the treasury".into(),
+                content_hash: vec![1; 32],
+                url: "https://example.com/proposals/1".into(),
+            };
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call.clone(), metadata.clone())
+                .unwrap();
+            assert_eq!(
+                superdao.get_proposal(proposal_id).map(|p| p.metadata),
+                Some(metadata)
+            );
+
+            let too_long = ProposalMetadata {
+                title: "a".repeat(ProposalMetadata::MAX_TITLE_LEN + 1),
+                ..Default::default()
+            };
+            assert_eq!(
+                superdao.create_proposal(call, too_long),
+                Err(Error::MetadataTooLong)
+            );
+        }
+
+        #[ink::test]
+        fn create_chain_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let location = Location::here();
+            let msg: Xcm<()> = Xcm::new();
+            let call = Call::Chain(ChainCall::new(&location, &msg));
+
+            assert!(superdao.register_member().is_ok());
+            assert!(superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .is_ok());
+            assert_eq!(
+                superdao.proposals.get(superdao.next_id - 1),
+                Some(Proposal {
+                    proposer: accounts.alice,
+                    call,
+                    metadata: ProposalMetadata::default(),
+                    voting_period_end: 0,
+                    electorate_size: 1,
+                })
+            );
+            assert_eq!(superdao.active_proposals.len(), 1);
+        }
+
+        #[ink::test]
+        fn create_runtime_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            // `System::remark` with an empty remark
+            let call = Call::Runtime(vec![0, 0, 0]);
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(
+                superdao.get_proposal(proposal_id),
+                Some(Proposal {
+                    proposer: accounts.alice,
+                    call,
+                    metadata: ProposalMetadata::default(),
+                    voting_period_end: 0,
+                    electorate_size: 1,
+                })
+            );
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+        }
+
+        #[ink::test]
+        fn runtime_call_is_passed_unprefixed() {
+            // the part of a runtime's `RuntimeCall` needed for `System::remark`
+            #[ink::scale_derive(Encode)]
+            enum RuntimeCall {
+                #[codec(index = 0)]
+                System(SystemCall),
+            }
+            #[ink::scale_derive(Encode)]
+            enum SystemCall {
+                #[codec(index = 0)]
+                Remark { remark: Vec<u8> },
+            }
+
+            let call = RuntimeCall::System(SystemCall::Remark {
+                remark: b"superdao".to_vec(),
+            })
+            .encode();
+            assert_eq!(&call[..3], &[0, 0, 8 << 2]);
+            assert_eq!(&call[3..], b"superdao");
+            // `call_runtime` encodes its argument, which must not add a length prefix
+            assert_eq!(CallInput(&call).encode(), call);
+            assert_ne!(call.encode(), call);
+        }
+
+        #[ink::test]
+        fn instantiate_proposal_records_address() {
+            let mut superdao = Superdao::default();
+            let contract = ink::env::test::callee::<Environment>();
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            let instantiate = |salt: u8, endowment: Balance| {
+                Call::Instantiate(InstantiateCall {
+                    code_hash: Hash::from([7; 32]),
+                    selector: [0; 4],
+                    input: vec![],
+                    endowment,
+                    salt: vec![salt],
+                    ref_time_limit: 0,
+                    proof_size_limit: 0,
+                })
+            };
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Batch {
+                        calls: vec![instantiate(1, 0), instantiate(2, 0), instantiate(3, 1_001)],
+                        atomic: false,
+                    },
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: false })
+            );
+
+            // each salt yields its own address, the DAO cannot pay the last endowment
+            let results = superdao.get_execution_results(proposal_id);
+            let (CallResult::Instantiated(first), CallResult::Instantiated(second)) =
+                (results[0], results[1])
+            else {
+                panic!("unexpected results: {results:?}");
+            };
+            assert_ne!(first, second);
+            assert_eq!(results[2], CallResult::Failed);
+        }
+
+        #[ink::test]
+        fn vote_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            assert!(superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .is_ok());
+
+            assert!(superdao.vote(superdao.next_id - 1, Vote::Aye).is_ok());
+
+            assert_eq!(
+                superdao.votes.get(superdao.next_id - 1),
+                Some(vec![(accounts.alice, Vote::Aye, 1)])
+            );
+        }
+
+        // TODO: write this test with e2e tests
+        #[ink::test]
+        fn resolve_proposal_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            assert!(superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .is_ok());
+            assert!(superdao.vote(superdao.next_id - 1, Vote::Aye).is_ok());
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert!(superdao.resolve_proposal(superdao.next_id - 1).is_ok());
+            assert!(superdao.proposals.contains(superdao.next_id - 1));
+            assert_eq!(
+                superdao.statuses.get(superdao.next_id - 1),
+                Some(ProposalStatus::Executed { success: true })
+            );
+            assert_eq!(superdao.active_proposals.len(), 0);
+        }
+
+        #[ink::test]
+        fn resolve_proposal_checks_quorum_and_approval() {
+            let rule = GovernanceRule {
+                quorum: Perbill::from_percent(50),
+                approval: Perbill::from_percent(60),
+                reveal_period: 0,
+                supermajority: None,
+                enactment_delay: 0,
+                execution_window: 0,
+                veto: None,
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            for member in [
+                accounts.alice,
+                accounts.bob,
+                accounts.charlie,
+                accounts.django,
+            ] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                assert!(superdao.register_member().is_ok());
+            }
+
+            // 1 out of 4 members voted: quorum not reached
+            let first = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(first, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(first),
+                Ok(ProposalStatus::Rejected)
+            );
+
+            // quorum reached but only 50% ayes
+            let second = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(second, Vote::Nay).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(second, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(second),
+                Ok(ProposalStatus::Rejected)
+            );
+
+            // quorum reached with 2 out of 3 ayes
+            let third = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            for member in [accounts.alice, accounts.bob] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                assert!(superdao.vote(third, Vote::Aye).is_ok());
+            }
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(superdao.vote(third, Vote::Nay).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(third),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+        }
+
+        #[ink::test]
+        fn vote_requires_membership_at_proposal_creation() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            assert_eq!(
+                superdao.vote(proposal_id, Vote::Aye),
+                Err(Error::NotInElectorate)
+            );
+        }
+
+        #[ink::test]
+        fn deregister_member_withdraws_votes() {
+            let mut superdao = Superdao::new(
+                1,
+                0,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+
+            assert!(superdao.deregister_member().is_ok());
+            assert!(superdao.get_votes(proposal_id).is_empty());
+
+            // rejoining does not grant a vote on proposals created in between
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(superdao.register_member().is_ok());
+            assert_eq!(
+                superdao.vote(proposal_id, Vote::Aye),
+                Err(Error::NotInElectorate)
+            );
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Rejected)
+            );
+        }
+
+        #[ink::test]
+        fn abstain_counts_toward_quorum_only() {
+            let rule = GovernanceRule {
+                quorum: Perbill::from_percent(100),
+                approval: Perbill::from_percent(100),
+                reveal_period: 0,
+                supermajority: None,
+                enactment_delay: 0,
+                execution_window: 0,
+                veto: None,
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Abstain).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+        }
+
+        #[ink::test]
+        fn delegation_works() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            for member in [accounts.alice, accounts.bob, accounts.charlie] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                assert!(superdao.register_member().is_ok());
+            }
+            // charlie -> bob -> alice
+            assert!(superdao.delegate(accounts.bob).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.delegate(accounts.alice).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                superdao.delegate(accounts.charlie),
+                Err(Error::DelegationCycle)
+            );
+            assert_eq!(superdao.delegate(accounts.django), Err(Error::NotMember));
+
+            let proposal_id = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.get_tally(proposal_id),
+                Tally {
+                    ayes: 3,
+                    nays: 0,
+                    abstains: 0,
+                    turnout: 3,
+                }
+            );
+
+            // a direct vote overrides the delegation
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.vote(proposal_id, Vote::Nay).is_ok());
+            assert_eq!(
+                superdao.get_tally(proposal_id),
+                Tally {
+                    ayes: 1,
+                    nays: 2,
+                    abstains: 0,
+                    turnout: 3,
+                }
+            );
+
+            // delegation changes only apply to proposals created afterwards
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(superdao.undelegate().is_ok());
+            assert_eq!(superdao.undelegate(), Err(Error::NotDelegating));
+            assert_eq!(superdao.get_tally(proposal_id).turnout, 3);
+
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.delegate(accounts.alice).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.get_tally(proposal_id),
+                Tally {
+                    ayes: 2,
+                    nays: 0,
+                    abstains: 0,
+                    turnout: 2,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn conviction_voting_works() {
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::LockWeighted,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let contract = ink::env::test::callee::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(
+                superdao.vote_with_conviction(proposal_id, Vote::Aye, Conviction::Locked3x),
+                Err(Error::NothingLocked)
+            );
+            assert_eq!(
+                superdao.vote(proposal_id, Vote::Aye),
+                Err(Error::InvalidVotingMode)
+            );
+
+            ink::env::test::set_value_transferred::<Environment>(500);
+            assert!(superdao
+                .vote_with_conviction(proposal_id, Vote::Aye, Conviction::Locked3x)
+                .is_ok());
+            assert_eq!(superdao.get_tally(proposal_id).ayes, 1_500);
+            // voting ends at block 10, locked for 2 more voting periods
+            assert_eq!(superdao.get_lock(accounts.alice), Some((500, 30)));
+
+            assert_eq!(superdao.unlock(), Err(Error::LockNotExpired));
+            for _ in 0..30 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            ink::env::test::set_account_balance::<Environment>(contract, 1_000);
+            assert_eq!(superdao.unlock(), Ok(500));
+            assert_eq!(superdao.get_lock(accounts.alice), None);
+            assert_eq!(superdao.unlock(), Err(Error::NothingLocked));
+        }
+
+        #[ink::test]
+        fn conviction_requires_lock_weighted_voting() {
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            // the locked value is not what these modes weigh votes by
+            for voting_mode in [
+                VotingMode::OneMemberOneVote,
+                VotingMode::TokenWeighted {
+                    token: accounts.django,
+                },
+            ] {
+                let mut superdao = Superdao::new(
+                    0,
+                    10,
+                    GovernanceRule::default(),
+                    0,
+                    MembershipMode::Open,
+                    MembershipPolicy::Any,
+                    voting_mode,
+                )
+                .unwrap();
+                assert!(superdao.register_member().is_ok());
+                let proposal_id = superdao
+                    .create_proposal(call.clone(), ProposalMetadata::default())
+                    .unwrap();
+
+                ink::env::test::set_value_transferred::<Environment>(400);
+                assert_eq!(
+                    superdao.vote_with_conviction(proposal_id, Vote::Aye, Conviction::Locked3x),
+                    Err(Error::InvalidVotingMode)
+                );
+                ink::env::test::set_value_transferred::<Environment>(0);
+            }
+        }
+
+        #[ink::test]
+        fn quadratic_voting_works() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::Quadratic {
+                    credits: 100,
+                    epoch: 10,
+                },
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            let first = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            let second = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(
+                superdao.vote(first, Vote::Aye),
+                Err(Error::InvalidVotingMode)
+            );
+
+            assert!(superdao.vote_with_credits(first, Vote::Aye, 64).is_ok());
+            assert_eq!(superdao.get_tally(first).ayes, 8);
+            assert_eq!(superdao.get_remaining_credits(accounts.alice, second), 36);
+            assert_eq!(
+                superdao.vote_with_credits(second, Vote::Aye, 37),
+                Err(Error::InsufficientCredits)
+            );
+
+            // changing a vote gives back the credits spent on it
+            assert!(superdao.vote_with_credits(first, Vote::Nay, 16).is_ok());
+            assert_eq!(superdao.get_tally(first).nays, 4);
+            assert!(superdao.vote_with_credits(second, Vote::Aye, 84).is_ok());
+            assert_eq!(superdao.get_remaining_credits(accounts.alice, second), 0);
+
+            // credits are granted again in the next epoch
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            let third = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(superdao.get_remaining_credits(accounts.alice, third), 100);
+        }
+
+        #[ink::test]
+        fn quadratic_voting_rejects_delegation() {
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::Quadratic {
+                    credits: 100,
+                    epoch: 10,
+                },
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            assert_eq!(
+                superdao.delegate(accounts.alice),
+                Err(Error::InvalidVotingMode)
+            );
+            assert_eq!(superdao.get_delegation(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn commit_reveal_voting_works() {
+            let rule = GovernanceRule {
+                reveal_period: 5,
+                ..Default::default()
+            };
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let salt = [42; 32];
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(
+                superdao.vote(proposal_id, Vote::Aye),
+                Err(Error::InvalidVotingMode)
+            );
+            let commitment = vote_commitment(
+                proposal_id,
+                &accounts.alice,
+                &Vote::Aye,
+                Conviction::None,
+                &salt,
+            );
+            assert!(superdao.commit_vote(proposal_id, commitment).is_ok());
+            assert!(superdao.get_votes(proposal_id).is_empty());
+            assert_eq!(
+                superdao.reveal_vote(proposal_id, Vote::Aye, Conviction::None, salt),
+                Err(Error::VotingPeriodNotEnded)
+            );
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.commit_vote(proposal_id, commitment),
+                Err(Error::VotingPeriodEnded)
+            );
+            assert_eq!(
+                superdao.reveal_vote(proposal_id, Vote::Nay, Conviction::None, salt),
+                Err(Error::InvalidReveal)
+            );
+            assert!(superdao
+                .reveal_vote(proposal_id, Vote::Aye, Conviction::None, salt)
+                .is_ok());
+            assert_eq!(
+                superdao.get_votes(proposal_id),
+                vec![(accounts.alice, Vote::Aye)]
+            );
+            assert_eq!(
+                superdao.reveal_vote(proposal_id, Vote::Aye, Conviction::None, salt),
+                Err(Error::NoCommitment)
+            );
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Err(Error::VotingPeriodNotEnded)
+            );
+
+            for _ in 0..5 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+        }
+
+        #[ink::test]
+        fn commit_reveal_voting_with_conviction_works() {
+            let rule = GovernanceRule {
+                reveal_period: 5,
+                ..Default::default()
+            };
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::LockWeighted,
+            )
+            .unwrap();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let salt = [42; 32];
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            let commitment = vote_commitment(
+                proposal_id,
+                &accounts.alice,
+                &Vote::Aye,
+                Conviction::Locked2x,
+                &salt,
+            );
+            // the conviction stays secret, the value is locked until the end of the reveal period
+            ink::env::test::set_value_transferred::<Environment>(500);
+            assert!(superdao.commit_vote(proposal_id, commitment).is_ok());
+            assert_eq!(superdao.get_lock(accounts.alice), Some((500, 15)));
+            ink::env::test::set_value_transferred::<Environment>(0);
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.reveal_vote(proposal_id, Vote::Aye, Conviction::Locked3x, salt),
+                Err(Error::InvalidReveal)
+            );
+            assert!(superdao
+                .reveal_vote(proposal_id, Vote::Aye, Conviction::Locked2x, salt)
+                .is_ok());
+            assert_eq!(superdao.get_tally(proposal_id).ayes, 1_000);
+            // locked for one more voting period after the reveal period
+            assert_eq!(superdao.get_lock(accounts.alice), Some((500, 25)));
+        }
+
+        #[ink::test]
+        fn submit_signed_votes_works() {
+            let mut superdao = Superdao::default();
+            let call = Call::Contract(ContractCall {
+                callee: AccountId::from([1; 32]),
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let decode = |hex: &str| hex::decode(hex).expect("valid hex");
+            // ballots for proposal 0 of the DAO at [1; 32] with nonce 0, signed by an sr25519
+            // key and an ecdsa key whose account is the blake2 hash of its public key
+            let sr25519_voter = AccountId::try_from(
+                &decode("6023d052711f478bc758918bdd26e24ac5d773629fc902dfb09de2588ba4ab08")[..],
+            )
+            .unwrap();
+            let sr25519_signature = Signature::Sr25519(
+                decode(
+                    "dca716f6f98127f7185225a2b9796b9bcb4b50ff912f55eec753391605541869\
+                     b28e5e96bd68ebe31bbbc6cf926746b01d76153439ca8bd4791583822d864882",
+                )
+                .try_into()
+                .unwrap(),
+            );
+            let ecdsa_voter = AccountId::try_from(
+                &decode("4b9feced8f3a8ca1610230cf330053ea8119bacaab5736f336717c7d48366749")[..],
+            )
+            .unwrap();
+            let ecdsa_signature = Signature::Ecdsa(
+                decode(
+                    "a389e6711df2393b93c560b1f711d40d297a6baf5a1e1b10972b7b65b0a34afe\
+                     235051f267bb2e76490451b62b794f4f6f8e93c51b61bb040b82aef27d88c43b00",
+                )
+                .try_into()
+                .unwrap(),
+            );
+
+            for member in [sr25519_voter, ecdsa_voter] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                assert!(superdao.register_member().is_ok());
+            }
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(proposal_id, 0);
+            assert_eq!(
+                ink::env::test::callee::<Environment>(),
+                AccountId::from([1; 32])
+            );
+
+            let ballots = vec![
+                (sr25519_voter, Vote::Aye, sr25519_signature),
+                (ecdsa_voter, Vote::Nay, ecdsa_signature),
+            ];
+            assert!(superdao
+                .submit_signed_votes(proposal_id, ballots.clone())
+                .is_ok());
+            assert_eq!(
+                superdao.get_votes(proposal_id),
+                vec![(sr25519_voter, Vote::Aye), (ecdsa_voter, Vote::Nay)]
+            );
+            assert_eq!(superdao.get_ballot_nonce(sr25519_voter), 1);
+            assert_eq!(superdao.get_ballot_nonce(ecdsa_voter), 1);
+
+            // the nonce has moved on, so the same ballots cannot be replayed
+            for ballot in ballots {
+                assert_eq!(
+                    superdao.submit_signed_votes(proposal_id, vec![ballot]),
+                    Err(Error::InvalidSignature)
+                );
+            }
+            assert_eq!(superdao.get_ballot_nonce(sr25519_voter), 1);
+        }
+
+        #[ink::test]
+        fn submit_signed_votes_rejects_invalid_ballots() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+
+            // anyone can relay ballots, but only for members
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(
+                superdao.submit_signed_votes(
+                    proposal_id,
+                    vec![(accounts.bob, Vote::Aye, Signature::Sr25519([0; 64]))]
+                ),
+                Err(Error::NotMember)
+            );
+            assert_eq!(
+                superdao.submit_signed_votes(
+                    proposal_id,
+                    vec![(accounts.alice, Vote::Aye, Signature::Ecdsa([0; 65]))]
+                ),
+                Err(Error::InvalidSignature)
+            );
+            assert!(superdao.get_votes(proposal_id).is_empty());
+            assert_eq!(superdao.get_ballot_nonce(accounts.alice), 0);
+        }
+
+        #[ink::test]
+        fn token_weighted_voting_works() {
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let rule = GovernanceRule {
+                quorum: Perbill::default(),
+                approval: Perbill::from_percent(50),
+                reveal_period: 0,
+                supermajority: None,
+                enactment_delay: 0,
+                execution_window: 0,
+                veto: None,
+            };
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::TokenWeighted {
+                    token: accounts.frank,
+                },
+            )
+            .unwrap();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            ink::env::test::set_account_balance::<Environment>(accounts.alice, 100);
+            ink::env::test::set_account_balance::<Environment>(accounts.bob, 50);
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(proposal_id, Vote::Nay).is_ok());
+
+            assert_eq!(
+                superdao.votes.get(proposal_id),
+                Some(vec![
+                    (accounts.bob, Vote::Aye, 50),
+                    (accounts.alice, Vote::Nay, 100)
+                ])
+            );
+            // one out of two voters approves, but only a third of the weight does
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Rejected)
+            );
+        }
+
+        #[ink::test]
+        fn resolve_proposal_early_on_supermajority() {
+            let rule = GovernanceRule {
+                approval: Perbill::from_percent(50),
+                supermajority: Some(Perbill::from_percent(66)),
+                ..Default::default()
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+            for member in [accounts.alice, accounts.bob, accounts.charlie] {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                assert!(superdao.register_member().is_ok());
+            }
+            let approved = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            let rejected = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.vote(approved, Vote::Aye).is_ok());
+            assert!(superdao.vote(rejected, Vote::Nay).is_ok());
+            // a single member is no supermajority
+            assert_eq!(
+                superdao.resolve_proposal(approved),
+                Err(Error::VotingPeriodNotEnded)
+            );
+            assert_eq!(
+                superdao.resolve_proposal(rejected),
+                Err(Error::VotingPeriodNotEnded)
+            );
+
+            // charlie's vote cannot turn the outcome anymore
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.vote(approved, Vote::Aye).is_ok());
+            assert!(superdao.vote(rejected, Vote::Nay).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(approved),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+            assert_eq!(
+                superdao.resolve_proposal(rejected),
+                Ok(ProposalStatus::Rejected)
+            );
+        }
+
+        #[ink::test]
+        fn execute_proposal_after_enactment_delay() {
+            let rule = GovernanceRule {
+                enactment_delay: 5,
+                ..Default::default()
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(
+                    Call::Internal(InternalCall::RemoveMember(accounts.alice)),
+                    ProposalMetadata::default(),
+                )
+                .unwrap();
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Err(Error::ProposalIsNotApproved)
+            );
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Approved)
+            );
+            assert_eq!(superdao.get_enactment_block(proposal_id), Some(5));
+            // nothing is dispatched before the delay passed
+            assert!(superdao.is_member());
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Err(Error::EnactmentDelayNotPassed)
+            );
+
+            for _ in 0..5 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+            assert!(!superdao.is_member());
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Err(Error::ProposalIsNotApproved)
+            );
+        }
+
+        #[ink::test]
+        fn proposal_expires_after_execution_window() {
+            let rule = GovernanceRule {
+                enactment_delay: 5,
+                execution_window: 10,
+                ..Default::default()
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+
+            assert!(superdao.register_member().is_ok());
+            let unresolved = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            let unexecuted = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(unresolved, Vote::Aye).is_ok());
+            assert!(superdao.vote(unexecuted, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(unexecuted),
+                Ok(ProposalStatus::Approved)
+            );
+
+            assert_eq!(superdao.get_enactment_block(unexecuted), Some(5));
+
+            for _ in 0..15 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            // lapsed proposals are no longer listed or open to votes, even before they are
+            // marked as expired
+            assert!(superdao.get_active_proposals().is_empty());
+            assert!(superdao.get_proposals().is_empty());
+            assert_eq!(
+                superdao.get_proposal_status(unresolved),
+                Some(ProposalStatus::Expired)
+            );
+            assert_eq!(
+                superdao.vote(unresolved, Vote::Nay),
+                Err(Error::AlreadyResolved)
+            );
+            assert_eq!(
+                superdao.resolve_proposal(unresolved),
+                Ok(ProposalStatus::Expired)
+            );
+            assert_eq!(
+                superdao.execute_proposal(unexecuted),
+                Ok(ProposalStatus::Expired)
+            );
+            assert_eq!(
+                superdao.get_proposal_status(unexecuted),
+                Some(ProposalStatus::Expired)
+            );
+            assert_eq!(superdao.get_enactment_block(unexecuted), None);
+            assert_eq!(
+                superdao.execute_proposal(unexecuted),
+                Err(Error::ProposalIsNotApproved)
+            );
+        }
+
+        #[ink::test]
+        fn execution_window_follows_enactment_block() {
+            let rule = GovernanceRule {
+                enactment_delay: 5,
+                execution_window: 10,
+                ..Default::default()
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+
+            // resolved late, the proposal still gets its full enactment delay and window
+            for _ in 0..12 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Approved)
+            );
+            assert_eq!(superdao.get_enactment_block(proposal_id), Some(17));
+            for _ in 0..5 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
+            );
+        }
+
+        #[ink::test]
+        fn cancel_proposal_works() {
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
+
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            let cancelled = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            let late = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(superdao.cancel_proposal(cancelled), Err(Error::NotProposer));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.cancel_proposal(cancelled).is_ok());
+            assert_eq!(
+                superdao.get_proposal_status(cancelled),
+                Some(ProposalStatus::Cancelled)
+            );
+            assert_eq!(superdao.get_active_proposals(), vec![late]);
+            assert_eq!(
+                superdao.vote(cancelled, Vote::Aye),
+                Err(Error::AlreadyResolved)
+            );
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.cancel_proposal(late),
+                Err(Error::VotingPeriodEnded)
+            );
         }
 
-        #[ink(message)]
-        fn get_voting_period(&self) -> BlockNumber {
-            self.voting_period
-        }
+        #[ink::test]
+        fn veto_proposal_works() {
+            let rule = GovernanceRule {
+                enactment_delay: 5,
+                veto: Some(Perbill::from_percent(50)),
+                ..Default::default()
+            };
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+            let mut superdao = Superdao::new(
+                0,
+                0,
+                rule,
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
 
-        #[ink(message)]
-        fn get_active_proposals(&self) -> Vec<u32> {
-            self.active_proposals.clone()
-        }
-    }
+            assert!(superdao.register_member().is_ok());
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Approved)
+            );
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(superdao.veto_proposal(proposal_id), Err(Error::NotMember));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(superdao.veto_proposal(proposal_id).is_ok());
+            assert_eq!(
+                superdao.veto_proposal(proposal_id),
+                Err(Error::AlreadyVetoed)
+            );
+            assert_eq!(superdao.get_vetoes(proposal_id), vec![accounts.alice]);
+            assert_eq!(
+                superdao.get_proposal_status(proposal_id),
+                Some(ProposalStatus::Vetoed)
+            );
 
-        #[ink::test]
-        fn new_works() {
-            let superdao = Superdao::new(5, 4);
-            assert_eq!(superdao.members.len(), 0);
-            assert_eq!(superdao.active_proposals.len(), 0);
-            assert_eq!(superdao.next_id, 0);
-            assert_eq!(superdao.vote_threshold, 5);
-            assert_eq!(superdao.voting_period, 4);
+            for _ in 0..5 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(
+                superdao.execute_proposal(proposal_id),
+                Err(Error::ProposalIsNotApproved)
+            );
         }
 
         #[ink::test]
-        fn default_works() {
-            let superdao = Superdao::default();
-            assert_eq!(superdao.members.len(), 0);
-            assert_eq!(superdao.active_proposals.len(), 0);
-            assert_eq!(superdao.next_id, 0);
-            assert_eq!(superdao.vote_threshold, 0);
-            assert_eq!(superdao.voting_period, 0);
+        fn veto_proposal_requires_veto_rule() {
+            let mut superdao = Superdao::default();
+            let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
+
+            assert!(superdao.register_member().is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert_eq!(
+                superdao.veto_proposal(proposal_id),
+                Err(Error::VetoDisabled)
+            );
         }
 
         #[ink::test]
-        fn register_member_works() {
+        fn resolve_proposal_twice_fails() {
             let mut superdao = Superdao::default();
             let accounts = ink::env::test::default_accounts::<Environment>();
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
 
             assert!(superdao.register_member().is_ok());
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            assert!(superdao.register_member().is_ok());
-            assert_eq!(superdao.members.len(), 2);
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
+            assert!(superdao.resolve_proposal(proposal_id).is_ok());
+
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Err(Error::AlreadyResolved)
+            );
+            assert_eq!(
+                superdao.vote(proposal_id, Vote::Nay),
+                Err(Error::AlreadyResolved)
+            );
         }
 
         #[ink::test]
-        fn deregister_member_works() {
+        fn membership_emits_events() {
             let mut superdao = Superdao::default();
             let accounts = ink::env::test::default_accounts::<Environment>();
 
             assert!(superdao.register_member().is_ok());
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
-            assert!(superdao.register_member().is_ok());
+            assert!(superdao.deregister_member().is_ok());
+            assert_eq!(superdao.deregister_member(), Err(Error::NotMember));
 
-            superdao.deregister_member();
-            assert_eq!(superdao.members.len(), 1);
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
-            superdao.deregister_member();
-            assert_eq!(superdao.members.len(), 0);
+            let events: Vec<_> = ink::env::test::recorded_events().collect();
+            assert_eq!(events.len(), 2);
+            assert_eq!(
+                MemberRegistered::decode(&mut &events[0].data[..])
+                    .unwrap()
+                    .member,
+                accounts.alice
+            );
+            assert_eq!(
+                MemberDeregistered::decode(&mut &events[1].data[..])
+                    .unwrap()
+                    .member,
+                accounts.alice
+            );
         }
 
         #[ink::test]
-        fn create_contract_proposal_works() {
+        fn proposal_lifecycle_emits_events() {
             let mut superdao = Superdao::default();
             let accounts = ink::env::test::default_accounts::<Environment>();
             let call = Call::Contract(ContractCall {
@@ -337,40 +3998,100 @@ mod superdao {
             });
 
             assert!(superdao.register_member().is_ok());
-            assert!(superdao.create_proposal(call.clone()).is_ok());
+            let proposal_id = superdao
+                .create_proposal(call.clone(), ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Nay).is_ok());
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
             assert_eq!(
-                superdao.proposals.get(superdao.next_id - 1),
-                Some(Proposal {
-                    call,
-                    voting_period_end: 0
-                })
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Executed { success: true })
             );
-            assert_eq!(superdao.active_proposals.len(), 1);
+
+            let events: Vec<_> = ink::env::test::recorded_events().collect();
+            assert_eq!(events.len(), 6);
+
+            let created = ProposalCreated::decode(&mut &events[1].data[..]).unwrap();
+            assert_eq!(created.proposal_id, proposal_id);
+            assert_eq!(created.proposer, accounts.alice);
+            assert_eq!(created.call, call);
+
+            let cast = VoteCast::decode(&mut &events[2].data[..]).unwrap();
+            assert_eq!(cast.vote, Vote::Nay);
+            let changed = VoteChanged::decode(&mut &events[3].data[..]).unwrap();
+            assert_eq!(changed.vote, Vote::Aye);
+
+            assert_eq!(
+                ProposalApproved::decode(&mut &events[4].data[..])
+                    .unwrap()
+                    .proposal_id,
+                proposal_id
+            );
+            let dispatched = CallDispatched::decode(&mut &events[5].data[..]).unwrap();
+            assert_eq!(dispatched.proposal_id, proposal_id);
+            assert!(dispatched.success);
         }
 
         #[ink::test]
-        fn create_chain_proposal_works() {
-            let mut superdao = Superdao::default();
+        fn rejected_proposal_emits_event() {
+            let mut superdao = Superdao::new(
+                1,
+                0,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
             let accounts = ink::env::test::default_accounts::<Environment>();
-            let location = Location::here();
-            let msg: Xcm<()> = Xcm::new();
-            let call = Call::Chain(ChainCall::new(&location, &msg));
+            let call = Call::Contract(ContractCall {
+                callee: accounts.alice,
+                selector: [0; 4],
+                input: vec![],
+                transferred_value: 0,
+                ref_time_limit: 0,
+                allow_reentry: false,
+            });
 
             assert!(superdao.register_member().is_ok());
-            assert!(superdao.create_proposal(call.clone()).is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Nay).is_ok());
             assert_eq!(
-                superdao.proposals.get(superdao.next_id - 1),
-                Some(Proposal {
-                    call,
-                    voting_period_end: 0
-                })
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Rejected)
+            );
+
+            let events: Vec<_> = ink::env::test::recorded_events().collect();
+            let rejected = events.last().unwrap();
+            assert_eq!(
+                ProposalRejected::decode(&mut &rejected.data[..])
+                    .unwrap()
+                    .proposal_id,
+                proposal_id
             );
-            assert_eq!(superdao.active_proposals.len(), 1);
         }
 
         #[ink::test]
-        fn vote_works() {
+        fn resolve_unknown_proposal_fails() {
             let mut superdao = Superdao::default();
+            assert_eq!(superdao.resolve_proposal(0), Err(Error::ProposalNotFound));
+        }
+
+        #[ink::test]
+        fn resolve_proposal_before_voting_period_end_fails() {
+            let mut superdao = Superdao::new(
+                0,
+                10,
+                GovernanceRule::default(),
+                0,
+                MembershipMode::Open,
+                MembershipPolicy::Any,
+                VotingMode::OneMemberOneVote,
+            )
+            .unwrap();
             let accounts = ink::env::test::default_accounts::<Environment>();
             let call = Call::Contract(ContractCall {
                 callee: accounts.alice,
@@ -382,19 +4103,23 @@ mod superdao {
             });
 
             assert!(superdao.register_member().is_ok());
-            assert!(superdao.create_proposal(call).is_ok());
-
-            assert!(superdao.vote(superdao.next_id - 1, Vote::Aye).is_ok());
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+            assert!(superdao.vote(proposal_id, Vote::Aye).is_ok());
 
             assert_eq!(
-                superdao.votes.get(superdao.next_id - 1),
-                Some(vec![(accounts.alice, Vote::Aye)])
+                superdao.resolve_proposal(proposal_id),
+                Err(Error::VotingPeriodNotEnded)
             );
+            // nothing is taken out of storage
+            assert!(superdao.proposals.contains(proposal_id));
+            assert!(superdao.votes.contains(proposal_id));
+            assert_eq!(superdao.active_proposals, vec![proposal_id]);
         }
 
-        // TODO: write this test with e2e tests
         #[ink::test]
-        fn resolve_proposal_works() {
+        fn resolve_proposal_without_votes_rejects() {
             let mut superdao = Superdao::default();
             let accounts = ink::env::test::default_accounts::<Environment>();
             let call = Call::Contract(ContractCall {
@@ -407,14 +4132,16 @@ mod superdao {
             });
 
             assert!(superdao.register_member().is_ok());
-            assert!(superdao.create_proposal(call).is_ok());
-            assert!(superdao.vote(superdao.next_id - 1, Vote::Nay).is_ok());
-            for _ in 0..10 {
-                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
-            }
-            superdao.resolve_proposal(superdao.next_id - 1);
-            assert_eq!(superdao.proposals.get(superdao.next_id - 1), None);
-            assert_eq!(superdao.active_proposals.len(), 0);
+            let proposal_id = superdao
+                .create_proposal(call, ProposalMetadata::default())
+                .unwrap();
+
+            assert_eq!(
+                superdao.resolve_proposal(proposal_id),
+                Ok(ProposalStatus::Rejected)
+            );
+            assert!(superdao.proposals.contains(proposal_id));
+            assert!(superdao.active_proposals.is_empty());
         }
 
         mod super_dao_query {
@@ -458,13 +4185,18 @@ mod superdao {
                 });
 
                 assert!(superdao.register_member().is_ok());
-                assert!(superdao.create_proposal(call.clone()).is_ok());
+                assert!(superdao
+                    .create_proposal(call.clone(), ProposalMetadata::default())
+                    .is_ok());
 
                 assert_eq!(
                     superdao.get_proposal(superdao.next_id - 1),
                     Some(Proposal {
+                        proposer: accounts.alice,
                         call,
-                        voting_period_end: 0
+                        metadata: ProposalMetadata::default(),
+                        voting_period_end: 0,
+                        electorate_size: 1,
                     })
                 );
             }
@@ -483,7 +4215,8 @@ mod superdao {
                 });
 
                 assert!(superdao.register_member().is_ok());
-                let proposal_id = superdao.create_proposal(call.clone());
+                let proposal_id =
+                    superdao.create_proposal(call.clone(), ProposalMetadata::default());
                 assert_eq!(proposal_id, Ok(superdao.next_id - 1));
 
                 assert_eq!(
@@ -491,13 +4224,233 @@ mod superdao {
                     vec![(
                         0,
                         Proposal {
+                            proposer: accounts.alice,
                             call,
-                            voting_period_end: 0
+                            metadata: ProposalMetadata::default(),
+                            voting_period_end: 0,
+                            electorate_size: 1,
                         }
                     )]
                 );
             }
 
+            #[ink::test]
+            fn get_proposal_status_works() {
+                let mut superdao = Superdao::new(
+                    1,
+                    0,
+                    GovernanceRule::default(),
+                    0,
+                    MembershipMode::Open,
+                    MembershipPolicy::Any,
+                    VotingMode::OneMemberOneVote,
+                )
+                .unwrap();
+                let accounts = ink::env::test::default_accounts::<Environment>();
+                let call = Call::Contract(ContractCall {
+                    callee: accounts.alice,
+                    selector: [0; 4],
+                    input: vec![],
+                    transferred_value: 0,
+                    ref_time_limit: 0,
+                    allow_reentry: false,
+                });
+
+                assert!(superdao.register_member().is_ok());
+                let proposal_id = superdao
+                    .create_proposal(call, ProposalMetadata::default())
+                    .unwrap();
+                assert_eq!(
+                    superdao.get_proposal_status(proposal_id),
+                    Some(ProposalStatus::Active)
+                );
+
+                assert!(superdao.vote(proposal_id, Vote::Nay).is_ok());
+                assert!(superdao.resolve_proposal(proposal_id).is_ok());
+                assert_eq!(
+                    superdao.get_proposal_status(proposal_id),
+                    Some(ProposalStatus::Rejected)
+                );
+                assert_eq!(superdao.get_proposal_status(proposal_id + 1), None);
+            }
+
+            #[ink::test]
+            fn get_electorate_works() {
+                let mut superdao = Superdao::default();
+                let accounts = ink::env::test::default_accounts::<Environment>();
+                let call = Call::Contract(ContractCall {
+                    callee: accounts.alice,
+                    selector: [0; 4],
+                    input: vec![],
+                    transferred_value: 0,
+                    ref_time_limit: 0,
+                    allow_reentry: false,
+                });
+
+                assert!(superdao.register_member().is_ok());
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+                assert!(superdao.register_member().is_ok());
+                let proposal_id = superdao
+                    .create_proposal(call, ProposalMetadata::default())
+                    .unwrap();
+                assert!(superdao.deregister_member().is_ok());
+
+                assert_eq!(
+                    superdao.get_electorate(proposal_id),
+                    vec![accounts.alice, accounts.bob]
+                );
+                assert!(superdao.get_electorate(proposal_id + 1).is_empty());
+            }
+
+            #[ink::test]
+            fn get_voting_mode_works() {
+                let accounts = ink::env::test::default_accounts::<Environment>();
+                let mode = VotingMode::TokenWeighted {
+                    token: accounts.frank,
+                };
+                let superdao = Superdao::new(
+                    0,
+                    0,
+                    GovernanceRule::default(),
+                    0,
+                    MembershipMode::Open,
+                    MembershipPolicy::Any,
+                    mode,
+                )
+                .unwrap();
+                assert_eq!(superdao.get_voting_mode(), mode);
+            }
+
+            #[ink::test]
+            fn get_membership_deposit_works() {
+                let superdao = Superdao::new(
+                    0,
+                    0,
+                    GovernanceRule::default(),
+                    42,
+                    MembershipMode::Open,
+                    MembershipPolicy::Any,
+                    VotingMode::OneMemberOneVote,
+                )
+                .unwrap();
+                assert_eq!(superdao.get_membership_deposit(), 42);
+            }
+
+            #[ink::test]
+            fn get_membership_mode_works() {
+                let superdao = Superdao::new(
+                    0,
+                    0,
+                    GovernanceRule::default(),
+                    0,
+                    MembershipMode::ByVote,
+                    MembershipPolicy::Any,
+                    VotingMode::OneMemberOneVote,
+                )
+                .unwrap();
+                assert_eq!(superdao.get_membership_mode(), MembershipMode::ByVote);
+            }
+
+            #[ink::test]
+            fn get_membership_policy_works() {
+                let superdao = Superdao::new(
+                    0,
+                    0,
+                    GovernanceRule::default(),
+                    0,
+                    MembershipMode::Open,
+                    MembershipPolicy::ContractsOnly,
+                    VotingMode::OneMemberOneVote,
+                )
+                .unwrap();
+                assert_eq!(
+                    superdao.get_membership_policy(),
+                    MembershipPolicy::ContractsOnly
+                );
+            }
+
+            #[ink::test]
+            fn get_tally_works() {
+                let mut superdao = Superdao::default();
+                let accounts = ink::env::test::default_accounts::<Environment>();
+                let call = Call::Contract(ContractCall {
+                    callee: accounts.alice,
+                    selector: [0; 4],
+                    input: vec![],
+                    transferred_value: 0,
+                    ref_time_limit: 0,
+                    allow_reentry: false,
+                });
+
+                for member in [accounts.alice, accounts.bob, accounts.charlie] {
+                    ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                    assert!(superdao.register_member().is_ok());
+                }
+                let proposal_id = superdao
+                    .create_proposal(call, ProposalMetadata::default())
+                    .unwrap();
+                for (member, vote) in [
+                    (accounts.alice, Vote::Aye),
+                    (accounts.bob, Vote::Nay),
+                    (accounts.charlie, Vote::Abstain),
+                ] {
+                    ink::env::test::set_caller::<ink::env::DefaultEnvironment>(member);
+                    assert!(superdao.vote(proposal_id, vote).is_ok());
+                }
+
+                assert_eq!(
+                    superdao.get_tally(proposal_id),
+                    Tally {
+                        ayes: 1,
+                        nays: 1,
+                        abstains: 1,
+                        turnout: 3,
+                    }
+                );
+                assert_eq!(superdao.get_tally(proposal_id + 1), Tally::default());
+            }
+
+            #[ink::test]
+            fn get_delegation_works() {
+                let mut superdao = Superdao::default();
+                let accounts = ink::env::test::default_accounts::<Environment>();
+
+                assert!(superdao.register_member().is_ok());
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+                assert!(superdao.register_member().is_ok());
+                assert!(superdao.delegate(accounts.alice).is_ok());
+
+                assert_eq!(superdao.get_delegation(accounts.bob), Some(accounts.alice));
+                assert_eq!(superdao.get_delegation(accounts.alice), None);
+
+                assert!(superdao.deregister_member().is_ok());
+                assert_eq!(superdao.get_delegation(accounts.bob), None);
+            }
+
+            #[ink::test]
+            fn get_governance_rule_works() {
+                let rule = GovernanceRule {
+                    quorum: Perbill::from_percent(30),
+                    approval: Perbill::from_percent(51),
+                    reveal_period: 0,
+                    supermajority: None,
+                    enactment_delay: 0,
+                    execution_window: 0,
+                    veto: None,
+                };
+                let superdao = Superdao::new(
+                    0,
+                    0,
+                    rule,
+                    0,
+                    MembershipMode::Open,
+                    MembershipPolicy::Any,
+                    VotingMode::OneMemberOneVote,
+                )
+                .unwrap();
+                assert_eq!(superdao.get_governance_rule(), rule);
+            }
+
             #[ink::test]
             fn get_votes_works() {
                 let mut superdao = Superdao::default();
@@ -512,7 +4465,8 @@ mod superdao {
                 });
 
                 assert!(superdao.register_member().is_ok());
-                let proposal_id = superdao.create_proposal(call.clone());
+                let proposal_id =
+                    superdao.create_proposal(call.clone(), ProposalMetadata::default());
                 assert_eq!(proposal_id, Ok(superdao.next_id - 1));
                 assert!(superdao.vote(superdao.next_id - 1, Vote::Aye).is_ok());
                 assert_eq!(
@@ -547,4 +4501,200 @@ mod superdao {
             ink::env::debug_println!("msg: {:?}", hex::encode(chain_call.get_encoded_msg()));
         }
     }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::ContractsBackend;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn atomic_batch_reverts_storage<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            let mut constructor = SuperdaoRef::default();
+            let contract = client
+                .instantiate("superdao", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Superdao>();
+            let alice = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let dave = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+
+            let register_member = call_builder.register_member();
+            for signer in [ink_e2e::alice(), ink_e2e::bob()] {
+                client
+                    .call(&signer, &register_member)
+                    .submit()
+                    .await
+                    .expect("register_member failed");
+            }
+
+            // bob votes on another proposal, the removal of bob would withdraw that vote
+            let create_proposal = call_builder.create_proposal(
+                Call::Internal(InternalCall::AllowCodeHash(Hash::from([1; 32]))),
+                ProposalMetadata::default(),
+            );
+            let other_id = client
+                .call(&ink_e2e::alice(), &create_proposal)
+                .submit()
+                .await
+                .expect("create_proposal failed")
+                .return_value()
+                .expect("the proposal is created");
+            let vote = call_builder.vote(other_id, Vote::Aye);
+            client
+                .call(&ink_e2e::bob(), &vote)
+                .submit()
+                .await
+                .expect("vote failed");
+
+            let create_proposal = call_builder.create_proposal(
+                Call::Batch {
+                    calls: vec![
+                        Call::Internal(InternalCall::RemoveMember(bob)),
+                        Call::Internal(InternalCall::RemoveMember(dave)),
+                    ],
+                    atomic: true,
+                },
+                ProposalMetadata::default(),
+            );
+            let proposal_id = client
+                .call(&ink_e2e::alice(), &create_proposal)
+                .submit()
+                .await
+                .expect("create_proposal failed")
+                .return_value()
+                .expect("the proposal is created");
+            let vote = call_builder.vote(proposal_id, Vote::Aye);
+            for signer in [ink_e2e::alice(), ink_e2e::bob()] {
+                client
+                    .call(&signer, &vote)
+                    .submit()
+                    .await
+                    .expect("vote failed");
+            }
+
+            let resolve_proposal = call_builder.resolve_proposal(proposal_id);
+            let status = client
+                .call(&ink_e2e::alice(), &resolve_proposal)
+                .submit()
+                .await
+                .expect("resolve_proposal failed")
+                .return_value();
+            assert_eq!(status, Ok(ProposalStatus::Executed { success: false }));
+
+            let get_execution_results = call_builder.get_execution_results(proposal_id);
+            let results = client
+                .call(&ink_e2e::alice(), &get_execution_results)
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(results, vec![CallResult::Failed]);
+
+            // the removal of bob, including the vote written to a mapping, is reverted
+            let get_members = call_builder.get_members();
+            let members = client
+                .call(&ink_e2e::alice(), &get_members)
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(members, vec![alice, bob]);
+            let get_votes = call_builder.get_votes(other_id);
+            let votes = client
+                .call(&ink_e2e::alice(), &get_votes)
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(votes, vec![(bob, Vote::Aye)]);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn upgrade_batch_migrates<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+            let mut constructor = SuperdaoRef::default();
+            let contract = client
+                .instantiate("superdao", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Superdao>();
+            // the code is already uploaded by the instantiation, which only returns its hash
+            let code_hash = client
+                .upload("superdao", &ink_e2e::alice())
+                .submit()
+                .await
+                .expect("upload failed")
+                .code_hash;
+
+            let register_member = call_builder.register_member();
+            client
+                .call(&ink_e2e::alice(), &register_member)
+                .submit()
+                .await
+                .expect("register_member failed");
+
+            let create_proposal = call_builder.create_proposal(
+                Call::Batch {
+                    calls: vec![
+                        Call::Internal(InternalCall::SetCodeHash(code_hash)),
+                        Call::Contract(ContractCall {
+                            callee: contract.account_id,
+                            selector: ink::selector_bytes!("migrate"),
+                            input: vec![],
+                            transferred_value: 0,
+                            ref_time_limit: 0,
+                            allow_reentry: true,
+                        }),
+                    ],
+                    atomic: true,
+                },
+                ProposalMetadata::default(),
+            );
+            let proposal_id = client
+                .call(&ink_e2e::alice(), &create_proposal)
+                .submit()
+                .await
+                .expect("create_proposal failed")
+                .return_value()
+                .expect("the proposal is created");
+            let vote = call_builder.vote(proposal_id, Vote::Aye);
+            client
+                .call(&ink_e2e::alice(), &vote)
+                .submit()
+                .await
+                .expect("vote failed");
+
+            let resolve_proposal = call_builder.resolve_proposal(proposal_id);
+            let status = client
+                .call(&ink_e2e::alice(), &resolve_proposal)
+                .submit()
+                .await
+                .expect("resolve_proposal failed")
+                .return_value();
+            assert_eq!(status, Ok(ProposalStatus::Executed { success: true }));
+
+            let get_execution_results = call_builder.get_execution_results(proposal_id);
+            let results = client
+                .call(&ink_e2e::alice(), &get_execution_results)
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(results, vec![CallResult::Dispatched { success: true }; 2]);
+
+            let get_storage_version = call_builder.get_storage_version();
+            let version = client
+                .call(&ink_e2e::alice(), &get_storage_version)
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(version, STORAGE_VERSION);
+
+            Ok(())
+        }
+    }
 }
@@ -4,12 +4,13 @@ use ink::{
     env::{DefaultEnvironment, Environment},
     prelude::{string::String, vec::Vec},
     primitives::AccountId,
-    scale::{Decode, Encode, Output},
+    scale::{Compact, Decode, Encode, Output},
     xcm::prelude::*,
 };
 
 type Balance = <DefaultEnvironment as Environment>::Balance;
 type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+type Hash = <DefaultEnvironment as Environment>::Hash;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -19,6 +20,144 @@ pub enum Error {
     NotMember,
     ProposalNotFound,
     ProposalIsNotApproved,
+    VotingPeriodNotEnded,
+    NoVotes,
+    AlreadyResolved,
+    NotInElectorate,
+    InsufficientDeposit,
+    TransferFailed,
+    InvalidMembershipMode,
+    ApplicationPending,
+    NotContract,
+    CodeHashNotAllowed,
+    TokenQueryFailed,
+    DelegationCycle,
+    NotDelegating,
+    NothingLocked,
+    LockNotExpired,
+    InvalidVotingMode,
+    InsufficientCredits,
+    VotingPeriodEnded,
+    RevealPeriodEnded,
+    NoCommitment,
+    InvalidReveal,
+    InvalidSignature,
+    EnactmentDelayNotPassed,
+    NotProposer,
+    VetoDisabled,
+    AlreadyVetoed,
+    MetadataTooLong,
+    NotSelf,
+    BatchTooDeep,
+    SpendsReservedBalance,
+    MigrationMissing,
+}
+
+/// How the weight of a vote is determined.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum VotingMode {
+    /// Every member has a weight of one.
+    #[default]
+    OneMemberOneVote,
+    /// The weight of a vote is the voter's balance of a PSP22 token at the time of voting.
+    TokenWeighted { token: AccountId },
+    /// Members spend `credits` per `epoch` blocks through `vote_with_credits`, the weight of a
+    /// vote being the square root of the credits spent on it. With an `epoch` of zero the
+    /// credits are granted per proposal instead. Not available with commit-reveal voting.
+    Quadratic { credits: u128, epoch: BlockNumber },
+    /// The weight of a vote is the value locked with it through `vote_with_conviction` or
+    /// `commit_vote`, multiplied by its conviction. The only mode accepting convictions.
+    LockWeighted,
+}
+
+/// Commitment to a vote in commit-reveal voting, binding it to the proposal and the voter.
+/// The conviction is applied to the value locked by `commit_vote`.
+pub fn vote_commitment(
+    proposal_id: u32,
+    voter: &AccountId,
+    vote: &Vote,
+    conviction: Conviction,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(
+        &(proposal_id, voter, vote, conviction, salt),
+        &mut output,
+    );
+    output
+}
+
+/// Signature of a ballot, made with the key of the voting account.
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum Signature {
+    /// Signature over the ballot payload by the sr25519 key the account id is made of.
+    Sr25519([u8; 64]),
+    /// Signature over the blake2 256 hash of the ballot payload by an ecdsa key whose
+    /// blake2 256 hashed compressed public key is the account id.
+    Ecdsa([u8; 65]),
+}
+
+/// Payload a member signs to vote through a relayer. It is bound to the DAO contract, the
+/// proposal and the member's current ballot nonce so that it cannot be replayed.
+pub fn ballot_payload(dao: &AccountId, proposal_id: u32, vote: &Vote, nonce: u64) -> Vec<u8> {
+    (b"superdao/ballot", dao, proposal_id, vote, nonce).encode()
+}
+
+/// Weight of a quadratic vote, i.e. the integer square root of the credits spent.
+pub fn quadratic_weight(credits: u128) -> u128 {
+    if credits < 2 {
+        return credits;
+    }
+    // Newton's method, starting from an upper bound of the root
+    let mut x = 1u128 << ((128 - credits.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + credits / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Which accounts are allowed to become members.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum MembershipPolicy {
+    /// Any account can become a member.
+    #[default]
+    Any,
+    /// Only contracts can become members.
+    ContractsOnly,
+    /// Only contracts whose code hash is in the allowlist managed by governance can become
+    /// members.
+    AllowedCodeHashes,
+}
+
+/// How new members join the DAO.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum MembershipMode {
+    /// Anyone can join through `register_member`.
+    #[default]
+    Open,
+    /// Prospective members call `apply_for_membership` and are admitted by proposal. Only the
+    /// first member of an empty DAO can join through `register_member`.
+    ByVote,
 }
 
 #[derive(Clone, PartialEq)]
@@ -30,6 +169,209 @@ pub enum Error {
 pub enum Vote {
     Aye,
     Nay,
+    /// Counts toward the quorum but not toward the approval.
+    Abstain,
+}
+
+/// Multiplies the weight of a vote in exchange for locking value for a number of voting
+/// periods after the end of the vote.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, Eq))]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum Conviction {
+    /// Regular weight, nothing is locked.
+    #[default]
+    None,
+    Locked2x,
+    Locked3x,
+    Locked4x,
+    Locked5x,
+    Locked6x,
+}
+
+impl Conviction {
+    pub fn multiplier(self) -> u128 {
+        match self {
+            Conviction::None => 1,
+            Conviction::Locked2x => 2,
+            Conviction::Locked3x => 3,
+            Conviction::Locked4x => 4,
+            Conviction::Locked5x => 5,
+            Conviction::Locked6x => 6,
+        }
+    }
+
+    /// Number of voting periods the value stays locked after the end of the vote.
+    pub fn lock_periods(self) -> u32 {
+        match self {
+            Conviction::None => 0,
+            Conviction::Locked2x => 1,
+            Conviction::Locked3x => 2,
+            Conviction::Locked4x => 4,
+            Conviction::Locked5x => 8,
+            Conviction::Locked6x => 16,
+        }
+    }
+
+    /// Weight of a vote backed by `locked` value.
+    pub fn weight(self, locked: u128) -> u128 {
+        locked.saturating_mul(self.multiplier())
+    }
+}
+
+/// Votes of a proposal summed up by their weight.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, Eq))]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct Tally {
+    pub ayes: u128,
+    pub nays: u128,
+    pub abstains: u128,
+    /// Number of voters.
+    pub turnout: u32,
+}
+
+impl Tally {
+    pub fn add(&mut self, vote: &Vote, weight: u128) {
+        match vote {
+            Vote::Aye => self.ayes = self.ayes.saturating_add(weight),
+            Vote::Nay => self.nays = self.nays.saturating_add(weight),
+            Vote::Abstain => self.abstains = self.abstains.saturating_add(weight),
+        }
+        self.turnout = self.turnout.saturating_add(1);
+    }
+}
+
+/// A ratio expressed in parts per billion, e.g. the share of members that must vote on a
+/// proposal.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "std", derive(Debug, ink::storage::traits::StorageLayout))]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct Perbill(u32);
+
+impl Perbill {
+    pub const ACCURACY: u32 = 1_000_000_000;
+
+    /// Builds a ratio from parts per billion, saturating at 100%.
+    pub const fn from_parts(parts: u32) -> Self {
+        if parts > Self::ACCURACY {
+            Self(Self::ACCURACY)
+        } else {
+            Self(parts)
+        }
+    }
+
+    /// Builds a ratio from a percentage, saturating at 100%.
+    pub const fn from_percent(percent: u32) -> Self {
+        if percent > 100 {
+            Self(Self::ACCURACY)
+        } else {
+            Self(percent * (Self::ACCURACY / 100))
+        }
+    }
+
+    pub const fn deconstruct(self) -> u32 {
+        self.0
+    }
+
+    /// Returns whether `part / whole` is at least this ratio. A zero `whole` only reaches a
+    /// zero ratio.
+    pub fn is_reached_by(self, part: u128, whole: u128) -> bool {
+        if whole == 0 {
+            return self.0 == 0;
+        }
+        part.saturating_mul(Self::ACCURACY.into()) >= u128::from(self.0).saturating_mul(whole)
+    }
+}
+
+/// Rules deciding how votes are cast and when a proposal is approved.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct GovernanceRule {
+    /// Share of the members at proposal creation that must take part in the vote.
+    pub quorum: Perbill,
+    /// Share of Aye votes among Aye and Nay votes required for approval.
+    pub approval: Perbill,
+    /// Enables commit-reveal voting when non-zero: votes are committed during the voting
+    /// period and revealed within this many blocks after it.
+    pub reveal_period: BlockNumber,
+    /// Share of the members at proposal creation that lets a proposal be resolved before the
+    /// end of its voting period, once they all voted Aye (and the proposal is approved) or all
+    /// voted Nay, and the members yet to vote cannot change the outcome even with their highest
+    /// possible weight. Not available with commit-reveal or token-weighted voting.
+    pub supermajority: Option<Perbill>,
+    /// Blocks between the approval of a proposal and the earliest dispatch of its call through
+    /// `execute_proposal`. With no delay the call is dispatched on approval.
+    pub enactment_delay: BlockNumber,
+    /// Blocks after the enactment block of a proposal during which it can still be executed
+    /// before it expires. Until it is resolved, a proposal's enactment block is taken to be the
+    /// enactment delay after the end of its vote. Zero disables expiry.
+    pub execution_window: BlockNumber,
+    /// Share of the members at proposal creation whose vetoes reject a proposal at any point
+    /// before its call is dispatched. Vetoes are disabled without it.
+    pub veto: Option<Perbill>,
+}
+
+/// Lifecycle status of a proposal, kept after the proposal has been resolved.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum ProposalStatus {
+    /// Voting is ongoing or the proposal has not been resolved yet.
+    Active,
+    /// The proposal passed the vote and its call is waiting for the enactment delay to pass.
+    Approved,
+    /// The proposal did not pass the vote.
+    Rejected,
+    /// The call was dispatched; `success` reports the outcome of the dispatch.
+    Executed { success: bool },
+    /// The call could not be dispatched.
+    Failed,
+    /// The proposal can no longer be resolved or executed.
+    Expired,
+    /// The proposal was withdrawn by its proposer.
+    Cancelled,
+    /// The proposal was vetoed by members before its call was dispatched.
+    Vetoed,
+}
+
+/// Human readable description of a proposal.
+#[derive(Clone, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct ProposalMetadata {
+    pub title: String,
+    /// Hash of the full description, e.g. an IPFS CID or the blake2 hash of the text.
+    pub content_hash: Vec<u8>,
+    /// Where the full description can be found.
+    pub url: String,
+}
+
+impl ProposalMetadata {
+    pub const MAX_TITLE_LEN: usize = 128;
+    pub const MAX_CONTENT_HASH_LEN: usize = 64;
+    pub const MAX_URL_LEN: usize = 256;
+
+    /// Checks the length of each field in bytes.
+    pub fn ensure_bounded(&self) -> Result<(), Error> {
+        if self.title.len() > Self::MAX_TITLE_LEN
+            || self.content_hash.len() > Self::MAX_CONTENT_HASH_LEN
+            || self.url.len() > Self::MAX_URL_LEN
+        {
+            return Err(Error::MetadataTooLong);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -39,23 +381,107 @@ pub enum Vote {
 )]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
 pub struct Proposal {
+    pub proposer: AccountId,
     pub call: Call,
+    pub metadata: ProposalMetadata,
     pub voting_period_end: BlockNumber,
+    /// Number of members when the proposal was created, used to evaluate the quorum.
+    pub electorate_size: u32,
 }
 
 #[ink::trait_definition]
 pub trait SuperDao {
-    #[ink(message)]
+    /// Registers the caller as a member. The membership deposit has to be transferred along
+    /// with the call.
+    #[ink(message, payable)]
     fn register_member(&mut self) -> Result<(), Error>;
 
+    /// Removes the caller from the members and refunds its deposit.
     #[ink(message)]
-    fn deregister_member(&mut self);
+    fn deregister_member(&mut self) -> Result<(), Error>;
+
+    /// Creates a proposal to admit the caller as a member, for DAOs admitting members by vote.
+    /// The membership deposit has to be transferred along with the call. It is kept as the
+    /// member's deposit on admission and refunded if the application fails.
+    #[ink(message, payable)]
+    fn apply_for_membership(&mut self) -> Result<u32, Error>;
 
+    /// Submits a proposal to dispatch `call`, described by `metadata`.
     #[ink(message)]
-    fn create_proposal(&mut self, call: Call) -> Result<u32, Error>;
+    fn create_proposal(&mut self, call: Call, metadata: ProposalMetadata) -> Result<u32, Error>;
+
+    /// Withdraws a proposal of the caller before the end of its voting period.
+    #[ink(message)]
+    fn cancel_proposal(&mut self, proposal_id: u32) -> Result<(), Error>;
+
+    /// Vetoes a proposal that has not been dispatched yet, see [`GovernanceRule::veto`].
+    #[ink(message)]
+    fn veto_proposal(&mut self, proposal_id: u32) -> Result<(), Error>;
 
     #[ink(message)]
     fn vote(&mut self, proposal_id: u32, vote: Vote) -> Result<(), Error>;
+
+    /// Votes with the transferred value multiplied by `conviction` as weight, for DAOs weighing
+    /// votes by locked value. The transferred value is locked until the conviction's lock
+    /// periods have passed after the end of the vote.
+    #[ink(message, payable)]
+    fn vote_with_conviction(
+        &mut self,
+        proposal_id: u32,
+        vote: Vote,
+        conviction: Conviction,
+    ) -> Result<(), Error>;
+
+    /// Votes in quadratic voting mode by spending `credits`, replacing the credits spent on an
+    /// earlier vote on the same proposal.
+    #[ink(message)]
+    fn vote_with_credits(
+        &mut self,
+        proposal_id: u32,
+        vote: Vote,
+        credits: u128,
+    ) -> Result<(), Error>;
+
+    /// Commits to a vote in commit-reveal mode, see [`vote_commitment`]. DAOs weighing votes by
+    /// locked value lock the transferred value until the end of the reveal period, other DAOs
+    /// accept no value.
+    #[ink(message, payable)]
+    fn commit_vote(&mut self, proposal_id: u32, commitment: [u8; 32]) -> Result<(), Error>;
+
+    /// Reveals a committed vote after the end of the voting period. A conviction extends the
+    /// lock of the committed value like in `vote_with_conviction`, from the end of the reveal
+    /// period.
+    #[ink(message)]
+    fn reveal_vote(
+        &mut self,
+        proposal_id: u32,
+        vote: Vote,
+        conviction: Conviction,
+        salt: [u8; 32],
+    ) -> Result<(), Error>;
+
+    /// Records votes signed off-chain by members, see [`ballot_payload`]. Fails as a whole if
+    /// any of the ballots is invalid.
+    #[ink(message)]
+    fn submit_signed_votes(
+        &mut self,
+        proposal_id: u32,
+        ballots: Vec<(AccountId, Vote, Signature)>,
+    ) -> Result<(), Error>;
+
+    /// Returns the caller's locked value once its lock has expired.
+    #[ink(message)]
+    fn unlock(&mut self) -> Result<Balance, Error>;
+
+    /// Lets `to` vote on behalf of the caller on proposals the caller does not vote on.
+    /// Delegations only apply to proposals created afterwards and are not available in quadratic
+    /// voting mode.
+    #[ink(message)]
+    fn delegate(&mut self, to: AccountId) -> Result<(), Error>;
+
+    /// Withdraws the caller's delegation from proposals created afterwards.
+    #[ink(message)]
+    fn undelegate(&mut self) -> Result<(), Error>;
 }
 
 #[ink::trait_definition]
@@ -72,17 +498,75 @@ pub trait SuperDaoQuery {
     #[ink(message)]
     fn get_proposals(&self) -> Vec<(u32, Proposal)>;
 
+    #[ink(message)]
+    fn get_proposal_status(&self, proposal_id: u32) -> Option<ProposalStatus>;
+
+    /// Returns the block from which an approved proposal can be executed.
+    #[ink(message)]
+    fn get_enactment_block(&self, proposal_id: u32) -> Option<BlockNumber>;
+
+    /// Returns the outcome of each call dispatched by an executed proposal.
+    #[ink(message)]
+    fn get_execution_results(&self, proposal_id: u32) -> Vec<CallResult>;
+
     #[ink(message)]
     fn get_vote_threshold(&self) -> u8;
 
+    #[ink(message)]
+    fn get_voting_mode(&self) -> VotingMode;
+
+    #[ink(message)]
+    fn get_governance_rule(&self) -> GovernanceRule;
+
     #[ink(message)]
     fn get_voting_period(&self) -> BlockNumber;
 
+    #[ink(message)]
+    fn get_membership_deposit(&self) -> Balance;
+
+    #[ink(message)]
+    fn get_membership_mode(&self) -> MembershipMode;
+
+    #[ink(message)]
+    fn get_membership_policy(&self) -> MembershipPolicy;
+
+    #[ink(message)]
+    fn get_allowed_code_hashes(&self) -> Vec<Hash>;
+
     #[ink(message)]
     fn get_active_proposals(&self) -> Vec<u32>;
 
     #[ink(message)]
     fn get_votes(&self, proposal_id: u32) -> Vec<(AccountId, Vote)>;
+
+    #[ink(message)]
+    fn get_electorate(&self, proposal_id: u32) -> Vec<AccountId>;
+
+    #[ink(message)]
+    fn get_tally(&self, proposal_id: u32) -> Tally;
+
+    #[ink(message)]
+    fn get_delegation(&self, member: AccountId) -> Option<AccountId>;
+
+    /// Returns the credits `member` has left to spend on a proposal in quadratic voting mode.
+    #[ink(message)]
+    fn get_remaining_credits(&self, member: AccountId, proposal_id: u32) -> u128;
+
+    /// Returns the nonce the next signed ballot of `member` has to use.
+    #[ink(message)]
+    fn get_ballot_nonce(&self, member: AccountId) -> u64;
+
+    /// Returns the locked value of `account` and the block from which it can be unlocked.
+    #[ink(message)]
+    fn get_lock(&self, account: AccountId) -> Option<(Balance, BlockNumber)>;
+
+    /// Returns the members that vetoed a proposal.
+    #[ink(message)]
+    fn get_vetoes(&self, proposal_id: u32) -> Vec<AccountId>;
+
+    /// Returns the version of the storage layout, 0 if the storage predates versioning.
+    #[ink(message)]
+    fn get_storage_version(&self) -> u32;
 }
 
 #[derive(Clone)]
@@ -110,6 +594,30 @@ pub struct ContractCall {
     pub allow_reentry: bool,
 }
 
+/// Instantiation of a contract by the DAO, e.g. to spawn a sub-DAO or a vault.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct InstantiateCall {
+    /// Hash of the uploaded code of the contract.
+    pub code_hash: Hash,
+    /// The selector bytes that identifies the constructor that should be called.
+    pub selector: [u8; 4],
+    /// The SCALE encoded parameters that are passed to the constructor.
+    pub input: Vec<u8>,
+    /// The amount of chain balance that is transferred to the new contract.
+    pub endowment: Balance,
+    /// Salt used to derive the address of the new contract.
+    pub salt: Vec<u8>,
+    /// Gas limit for the execution of the constructor.
+    pub ref_time_limit: u64,
+    /// Limit of the proof size the execution of the constructor may add to the block.
+    pub proof_size_limit: u64,
+}
+
 #[derive(Clone)]
 #[cfg_attr(
     feature = "std",
@@ -148,6 +656,111 @@ impl ChainCall {
     }
 }
 
+/// Account a remote location is converted to when it dispatches calls on the chain hosting the
+/// DAO, matching `HashedDescription<AccountId32, DescribeFamily<DescribeAllTerminal>>` of
+/// `xcm-builder`. This is the account a member DAO on another chain registers and votes with.
+///
+/// Returns `None` for locations that are not described by that converter.
+pub fn derived_account(location: &Location) -> Option<AccountId> {
+    let description = match location.unpack() {
+        (0, [Parachain(index), tail @ ..]) => {
+            (b"ChildChain", Compact(*index), describe_terminal(tail)?).encode()
+        }
+        (1, [Parachain(index), tail @ ..]) => {
+            (b"SiblingChain", Compact(*index), describe_terminal(tail)?).encode()
+        }
+        (1, tail) => (b"ParentChain", describe_terminal(tail)?).encode(),
+        _ => return None,
+    };
+    let mut output = [0u8; 32];
+    ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&description, &mut output);
+    Some(output.into())
+}
+
+fn describe_terminal(junctions: &[Junction]) -> Option<Vec<u8>> {
+    match junctions {
+        [] => Some(Vec::new()),
+        [PalletInstance(index)] => Some((b"Pallet", Compact(u32::from(*index))).encode()),
+        [AccountId32 { id, .. }] => Some((b"AccountId32", id).encode()),
+        [AccountKey20 { key, .. }] => Some((b"AccountKey20", key).encode()),
+        _ => None,
+    }
+}
+
+/// Describes how `pallet-contracts` is reached on the chain hosting the DAO.
+#[derive(Clone, Debug)]
+pub struct ContractsPallet {
+    /// Index of `pallet-contracts` in the runtime of the chain hosting the DAO.
+    pub pallet_index: u8,
+    /// Gas limit of the contract call.
+    pub gas_limit: Weight,
+    /// Upper bound of the weight of the `Transact`, covering the gas limit and the overhead of
+    /// the dispatchable.
+    pub transact_weight: Weight,
+}
+
+impl ContractsPallet {
+    /// Call index of the `call` dispatchable of `pallet-contracts`.
+    pub const CALL_INDEX: u8 = 6;
+
+    /// Encodes a `pallet-contracts` `call` of `dao` passing `input` as call data.
+    pub fn encode_call(&self, dao: &AccountId, input: Vec<u8>) -> Vec<u8> {
+        let mut call = Vec::from([self.pallet_index, Self::CALL_INDEX]);
+        // dest: `MultiAddress::Id`
+        (0u8, dao).encode_to(&mut call);
+        // value
+        Compact(0u128).encode_to(&mut call);
+        // gas_limit
+        Compact(self.gas_limit.ref_time()).encode_to(&mut call);
+        Compact(self.gas_limit.proof_size()).encode_to(&mut call);
+        // storage_deposit_limit
+        Option::<Compact<Balance>>::None.encode_to(&mut call);
+        // data
+        input.encode_to(&mut call);
+        call
+    }
+}
+
+/// Builds the XCM message a member DAO on another chain sends to call `dao` with `input`.
+///
+/// `fees` are withdrawn from the member's derived account on the chain hosting the DAO to pay
+/// for the execution and what is left is deposited back to `member`, the location of the member
+/// as seen from that chain.
+pub fn remote_call_message(
+    pallet: &ContractsPallet,
+    dao: &AccountId,
+    input: Vec<u8>,
+    fees: Asset,
+    member: Location,
+) -> Xcm<()> {
+    Xcm::builder()
+        .withdraw_asset(fees.clone().into())
+        .buy_execution(fees, Unlimited)
+        .transact(
+            OriginKind::SovereignAccount,
+            pallet.transact_weight,
+            pallet.encode_call(dao, input).into(),
+        )
+        .refund_surplus()
+        .deposit_asset(Wild(AllCounted(1)), member)
+        .build()
+}
+
+/// Builds the XCM message a member DAO on another chain sends to vote on a proposal of `dao`,
+/// see [`remote_call_message`].
+pub fn remote_vote_message(
+    pallet: &ContractsPallet,
+    dao: &AccountId,
+    proposal_id: u32,
+    vote: Vote,
+    fees: Asset,
+    member: Location,
+) -> Xcm<()> {
+    let mut input = Vec::from(ink::selector_bytes!("SuperDao::vote"));
+    (proposal_id, vote).encode_to(&mut input);
+    remote_call_message(pallet, dao, input, fees, member)
+}
+
 #[derive(Clone)]
 #[cfg_attr(
     feature = "std",
@@ -157,9 +770,295 @@ impl ChainCall {
 pub enum Call {
     Contract(ContractCall),
     Chain(ChainCall),
+    Internal(InternalCall),
+    /// Encoded `RuntimeCall` of the chain hosting the DAO, dispatched through `call_runtime`.
+    Runtime(Vec<u8>),
+    /// Instantiates a contract, whose address is recorded in the execution results.
+    Instantiate(InstantiateCall),
+    /// Calls dispatched in order. If `atomic`, all of them are reverted when one fails,
+    /// otherwise the remaining calls are still dispatched.
+    Batch {
+        calls: Vec<Call>,
+        atomic: bool,
+    },
+}
+
+impl Call {
+    /// Number of batches nested in each other, zero for a call that is not a batch.
+    pub fn batch_depth(&self) -> u32 {
+        match self {
+            Call::Batch { calls, .. } => calls
+                .iter()
+                .map(Call::batch_depth)
+                .max()
+                .unwrap_or_default()
+                .saturating_add(1),
+            _ => 0,
+        }
+    }
+}
+
+/// Outcome of a single call dispatched by a proposal.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum CallResult {
+    /// The call was dispatched; `success` reports the outcome of the dispatch.
+    Dispatched { success: bool },
+    /// The call could not be dispatched, the instantiation failed or an atomic batch was
+    /// reverted.
+    Failed,
+    /// A contract was instantiated at the given address.
+    Instantiated(AccountId),
+}
+
+/// Calls handled by the DAO itself rather than dispatched to another contract or chain.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "std",
+    derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum InternalCall {
+    /// Admits an account as a member. The deposit escrowed with its application, if any,
+    /// becomes its membership deposit.
+    AddMember(AccountId),
+    /// Removes a member. Its deposit, if any, is kept by the DAO.
+    RemoveMember(AccountId),
+    /// Adds a code hash to the membership allowlist.
+    AllowCodeHash(Hash),
+    /// Removes a code hash from the membership allowlist.
+    DisallowCodeHash(Hash),
+    /// Upgrades the DAO to the uploaded code with the given hash. Only accepted when directly
+    /// followed, in the same atomic batch, by a call of the DAO's `migrate` message.
+    SetCodeHash(Hash),
 }
 
 // tests
+#[cfg(test)]
+mod perbill {
+    use super::*;
+    #[ink::test]
+    fn from_percent_works() {
+        assert_eq!(Perbill::from_percent(50).deconstruct(), 500_000_000);
+        assert_eq!(
+            Perbill::from_percent(200),
+            Perbill::from_parts(Perbill::ACCURACY)
+        );
+        assert_eq!(
+            Perbill::from_parts(u32::MAX).deconstruct(),
+            Perbill::ACCURACY
+        );
+    }
+
+    #[ink::test]
+    fn is_reached_by_works() {
+        let half = Perbill::from_percent(50);
+        assert!(half.is_reached_by(1, 2));
+        assert!(half.is_reached_by(2, 3));
+        assert!(!half.is_reached_by(1, 3));
+        assert!(!half.is_reached_by(0, 0));
+        assert!(Perbill::default().is_reached_by(0, 0));
+        assert!(Perbill::from_percent(100).is_reached_by(u128::MAX, u128::MAX));
+    }
+}
+
+#[cfg(test)]
+mod quadratic_weight {
+    use super::*;
+    #[ink::test]
+    fn is_integer_square_root() {
+        for (credits, weight) in [
+            (0, 0),
+            (1, 1),
+            (3, 1),
+            (4, 2),
+            (99, 9),
+            (100, 10),
+            (101, 10),
+        ] {
+            assert_eq!(quadratic_weight(credits), weight);
+        }
+        assert_eq!(quadratic_weight(u128::MAX), u64::MAX as u128);
+    }
+}
+
+#[cfg(test)]
+mod vote_commitment {
+    use super::*;
+    #[ink::test]
+    fn binds_all_inputs() {
+        let voter = AccountId::from([1; 32]);
+        let salt = [7; 32];
+        let none = Conviction::None;
+        let commitment = vote_commitment(0, &voter, &Vote::Aye, none, &salt);
+
+        assert_eq!(
+            commitment,
+            vote_commitment(0, &voter, &Vote::Aye, none, &salt)
+        );
+        assert_ne!(
+            commitment,
+            vote_commitment(1, &voter, &Vote::Aye, none, &salt)
+        );
+        assert_ne!(
+            commitment,
+            vote_commitment(0, &AccountId::from([2; 32]), &Vote::Aye, none, &salt)
+        );
+        assert_ne!(
+            commitment,
+            vote_commitment(0, &voter, &Vote::Nay, none, &salt)
+        );
+        assert_ne!(
+            commitment,
+            vote_commitment(0, &voter, &Vote::Aye, Conviction::Locked2x, &salt)
+        );
+        assert_ne!(
+            commitment,
+            vote_commitment(0, &voter, &Vote::Aye, none, &[8; 32])
+        );
+    }
+}
+
+#[cfg(test)]
+mod ballot_payload {
+    use super::*;
+    #[ink::test]
+    fn is_domain_separated() {
+        let dao = AccountId::from([1; 32]);
+        let payload = ballot_payload(&dao, 3, &Vote::Aye, 0);
+
+        assert!(payload.starts_with(b"superdao/ballot"));
+        assert_ne!(
+            payload,
+            ballot_payload(&AccountId::from([2; 32]), 3, &Vote::Aye, 0)
+        );
+        assert_ne!(payload, ballot_payload(&dao, 4, &Vote::Aye, 0));
+        assert_ne!(payload, ballot_payload(&dao, 3, &Vote::Nay, 0));
+        assert_ne!(payload, ballot_payload(&dao, 3, &Vote::Aye, 1));
+    }
+}
+
+#[cfg(test)]
+mod conviction {
+    use super::*;
+    #[ink::test]
+    fn lock_grows_with_multiplier() {
+        let convictions = [
+            Conviction::None,
+            Conviction::Locked2x,
+            Conviction::Locked3x,
+            Conviction::Locked4x,
+            Conviction::Locked5x,
+            Conviction::Locked6x,
+        ];
+        for pair in convictions.windows(2) {
+            assert!(pair[0].multiplier() < pair[1].multiplier());
+            assert!(pair[0].lock_periods() < pair[1].lock_periods());
+        }
+        assert_eq!(Conviction::default().lock_periods(), 0);
+    }
+
+    #[ink::test]
+    fn weight_scales_with_locked_value() {
+        assert_eq!(Conviction::None.weight(100), 100);
+        assert_eq!(Conviction::Locked3x.weight(0), 0);
+        assert_eq!(Conviction::Locked3x.weight(40), 120);
+        assert_eq!(Conviction::Locked6x.weight(100), 600);
+    }
+}
+
+#[cfg(test)]
+mod tally {
+    use super::*;
+    #[ink::test]
+    fn add_works() {
+        let mut tally = Tally::default();
+        tally.add(&Vote::Aye, 3);
+        tally.add(&Vote::Nay, 2);
+        tally.add(&Vote::Abstain, 1);
+        tally.add(&Vote::Aye, 4);
+
+        assert_eq!(
+            tally,
+            Tally {
+                ayes: 7,
+                nays: 2,
+                abstains: 1,
+                turnout: 4,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod remote {
+    use super::*;
+    #[ink::test]
+    fn derived_account_works() {
+        let member = |parents, para_id, id| {
+            Location::new(
+                parents,
+                [Parachain(para_id), AccountId32 { network: None, id }],
+            )
+        };
+
+        let account = derived_account(&member(1, 1000, [1; 32])).unwrap();
+        // xcm-builder describes the location as
+        // `(b"SiblingChain", Compact(1000u32), (b"AccountId32", [1u8; 32]).encode())`
+        // and hashes the description with blake2_256
+        assert_eq!(
+            account,
+            AccountId::from([
+                44, 147, 50, 130, 185, 240, 54, 1, 43, 9, 154, 137, 139, 131, 142, 186, 67, 3, 83,
+                223, 197, 121, 77, 122, 112, 62, 1, 192, 187, 190, 23, 244
+            ])
+        );
+        assert_eq!(derived_account(&member(1, 1000, [1; 32])), Some(account));
+        assert_ne!(derived_account(&member(1, 1001, [1; 32])), Some(account));
+        assert_ne!(derived_account(&member(1, 1000, [2; 32])), Some(account));
+        assert_ne!(derived_account(&member(0, 1000, [1; 32])), Some(account));
+        assert!(derived_account(&Location::new(1, [Parachain(1000)])).is_some());
+        assert!(derived_account(&Location::parent()).is_some());
+        assert_eq!(derived_account(&Location::here()), None);
+        assert_eq!(derived_account(&Location::new(2, Here)), None);
+    }
+
+    #[ink::test]
+    fn remote_vote_message_works() {
+        let pallet = ContractsPallet {
+            pallet_index: 40,
+            gas_limit: Weight::from_parts(1_000, 100),
+            transact_weight: Weight::from_parts(2_000, 200),
+        };
+        let dao = AccountId::from([1; 32]);
+        let fees: Asset = (Location::parent(), 10_000_000_000u128).into();
+        let member = Location::new(1, [Parachain(1000)]);
+
+        let msg = remote_vote_message(&pallet, &dao, 7, Vote::Aye, fees, member);
+        let Some(Transact {
+            origin_kind,
+            require_weight_at_most,
+            call,
+        }) = msg.0.iter().find(|x| matches!(x, Transact { .. }))
+        else {
+            panic!("message should contain a Transact");
+        };
+        assert_eq!(origin_kind, &OriginKind::SovereignAccount);
+        assert_eq!(require_weight_at_most, &pallet.transact_weight);
+
+        let mut input = Vec::from(ink::selector_bytes!("SuperDao::vote"));
+        (7u32, Vote::Aye).encode_to(&mut input);
+        let encoded = call.clone().into_encoded();
+        assert_eq!(encoded[..2], [40, ContractsPallet::CALL_INDEX]);
+        assert_eq!(encoded, pallet.encode_call(&dao, input.clone()));
+        assert!(encoded.ends_with(&input));
+    }
+}
+
 #[cfg(test)]
 mod chain_call {
     use super::*;